@@ -3,14 +3,14 @@ use aanyx::system::{System, FromRegistry};
 
 
 struct Name { name: String }
-impl FromRegistry<()> for Name {
+impl FromRegistry<'_, ()> for Name {
   fn from_registry( _: &() ) -> Name {
     black_box( Self { name: "Andrea".to_string() })
   }
 }
 
 struct Age{ age: u8 }
-impl FromRegistry<()> for Age {
+impl FromRegistry<'_, ()> for Age {
   fn from_registry( _: &() ) -> Age {
     black_box(Age { age: 7 })
   }
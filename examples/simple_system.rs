@@ -1,44 +1,44 @@
-use std::{env, rc::Rc};
+use std::{env, f64};
 use aanyx::system::{FromRegistry, System};
 use std::time::SystemTime;
 
 struct Person {
-  name: Rc<String>, 
-  age: Rc<u8>,
-  location: Rc<(f64, f64)>,
-  birthday: Rc<SystemTime>
+  name: String, 
+  age: u8,
+  location: (f64, f64),
+  birthday: SystemTime
 }
 
-struct Data<T> {
-  data: Rc<T>
+struct Data<'r, T> {
+  data: &'r T
 }
 
-impl FromRegistry<Person> for Data<String> {
-  fn from_registry( registry: &Person ) -> Self {
-    Self{ data: Rc::clone(&registry.name ) }
+impl<'r> FromRegistry<'r, Person> for Data<'r, String> {
+  fn from_registry( registry: &'r Person ) -> Self {
+    Self{ data: &registry.name }
   }
 }
 
-impl FromRegistry<Person> for Data<u8> {
-  fn from_registry( registry: &Person ) -> Self {
-    Self{ data: Rc::clone(&registry.age ) }
+impl<'r> FromRegistry<'r, Person> for Data<'r, u8> {
+  fn from_registry( registry: &'r Person ) -> Self {
+    Self{ data: &registry.age }
   }
 }
 
-impl FromRegistry<Person> for Data<(f64, f64)> {
-  fn from_registry( registry: &Person ) -> Self {
-    Self{ data: Rc::clone(&registry.location ) }
+impl<'r> FromRegistry<'r, Person> for Data<'r, (f64, f64)> {
+  fn from_registry( registry: &'r Person ) -> Self {
+    Self{ data: &registry.location }
   }
 }
 
-impl FromRegistry<Person> for Data<SystemTime> {
-  fn from_registry( registry: &Person ) -> Self {
-    Self{ data: Rc::clone(&registry.birthday ) }
+impl<'r> FromRegistry<'r, Person> for Data<'r, SystemTime> {
+  fn from_registry( registry: &'r Person ) -> Self {
+    Self{ data: &registry.birthday }
   }
 }
 
 fn main() {
-  let age = env::args().skip(1).next();
+  let age = env::args().nth(1);
   
     let person = match age {
       Some( age ) => {
@@ -51,19 +51,19 @@ fn main() {
           }
         };
         Person {
-          name: Rc::new(String::from("Jane")),
-          age: Rc::new( age ),
-          location: Rc::new( (0.0, 0.0) ),
-          birthday: Rc::new( SystemTime::now() )
+          name: String::from("Jane"),
+          age,
+          location: (0.0, 0.0),
+          birthday: SystemTime::now()
         }
       }
       None => { 
         println!("Run this passing 1 argument which is the age. Runninng with default value");
         Person { 
-          name: Rc::new(String::from("Andrea")),
-          age: Rc::new( 23 ),
-          location: Rc::new( (3.14159265, 2.7182818) ),
-          birthday: Rc::new( SystemTime::now() )
+          name: String::from("Andrea"),
+          age: 23,
+          location: (f64::consts::PI, f64::consts::E),
+          birthday: SystemTime::now()
         }
       }
     };
@@ -0,0 +1,384 @@
+//! Procedural macros for aanyx. Use them through the re-exports in the `aanyx` crate.
+
+mod stable;
+mod symbol;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Error, Expr, FnArg, GenericArgument, Ident, Item, ItemFn, ItemImpl, ItemTrait, Path, PathArguments, ReturnType, Signature, Token, TraitItem, TraitItemConst, TraitItemType, Type, TypePath, TypeTraitObject};
+
+// The options accepted by `#[plugin]`. All of them but `name` and `static_feature` are forwarded to the `with_` methods of the declaration.
+const OPTIONS: &[&str] = &["name", "id", "version", "dependencies", "init", "shutdown", "before_reload", "state_version", "export_state", "import_state", "fingerprint", "stable_abi", "metadata", "static_feature"];
+
+// The type requested by the host: a trait object or a concrete type
+enum PluginType {
+  Dyn( Path ),
+  Concrete( Path ),
+}
+
+impl PluginType {
+  // The name of the declaration, the same generated by `import_plugin!`
+  fn symbol( &self ) -> Ident {
+    format_ident!( "{}", symbol::declaration_symbol( self.to_token_stream() ) )
+  }
+}
+
+impl ToTokens for PluginType {
+  fn to_tokens( &self, tokens: &mut TokenStream2 ) {
+    match self {
+      Self::Dyn( path ) => tokens.extend( quote!( dyn #path ) ),
+      Self::Concrete( path ) => path.to_tokens( tokens ),
+    }
+  }
+}
+
+struct PluginOption {
+  key: Ident,
+  value: Expr,
+}
+
+impl Parse for PluginOption {
+  fn parse( input: ParseStream ) -> syn::Result<Self> {
+    let key: Ident = input.parse()?;
+    input.parse::<Token![=]>()?;
+    let value = input.parse()?;
+    Ok( Self { key, value } )
+  }
+}
+
+// `#[plugin( [dyn] Type, option = value, ... )]`, where the type is optional
+struct PluginArgs {
+  plugin_type: Option<(PluginType, Span)>,
+  options: Vec<PluginOption>,
+}
+
+impl Parse for PluginArgs {
+  fn parse( input: ParseStream ) -> syn::Result<Self> {
+    let mut plugin_type = None;
+    if input.peek( Token![dyn] ) {
+      let span = input.parse::<Token![dyn]>()?.span;
+      let path: Path = input.parse()?;
+      plugin_type = Some( (PluginType::Dyn( path ), span) );
+    } else if !input.is_empty() && !input.peek2( Token![=] ) {
+      let path: Path = input.parse()?;
+      let span = path.span();
+      plugin_type = Some( (PluginType::Concrete( path ), span) );
+    }
+    if plugin_type.is_some() && !input.is_empty() {
+      input.parse::<Token![,]>()?;
+    }
+
+    let options = Punctuated::<PluginOption, Token![,]>::parse_terminated( input )?.into_iter().collect::<Vec<_>>();
+    for (index, option) in options.iter().enumerate() {
+      if !OPTIONS.contains( &option.key.to_string().as_str() ) {
+        return Err( Error::new( option.key.span(), format!( "unknown option `{}`, expected one of: {}", option.key, OPTIONS.join( ", " ) ) ) );
+      }
+      if options[..index].iter().any( |previous| previous.key == option.key ) {
+        return Err( Error::new( option.key.span(), format!( "the option `{}` is given more than once", option.key ) ) );
+      }
+    }
+    Ok( Self { plugin_type, options } )
+  }
+}
+
+// What the attribute has been placed on: the plugin type, how to build the plugin and its default name
+struct Constructor {
+  plugin_type: PluginType,
+  plugin: TokenStream2,
+  name: String,
+}
+
+fn type_path( ty: &Type, message: &str ) -> syn::Result<Path> {
+  match ty {
+    Type::Path( TypePath { qself: None, path } ) => Ok( path.clone() ),
+    _ => Err( Error::new( ty.span(), message ) ),
+  }
+}
+
+fn last_ident( path: &Path ) -> String {
+  path.segments.last().expect( "a path has at least one segment" ).ident.to_string()
+}
+
+fn reject_type_argument( args: &PluginArgs, reason: &str ) -> syn::Result<()> {
+  match &args.plugin_type {
+    Some( (_, span) ) => Err( Error::new( *span, reason ) ),
+    None => Ok(()),
+  }
+}
+
+// `impl Trait for Type` registers `Type::default()` as `dyn Trait`, `impl Type` registers it as `Type`
+fn impl_constructor( args: &PluginArgs, item: &ItemImpl ) -> syn::Result<Constructor> {
+  if !item.generics.params.is_empty() {
+    return Err( Error::new( item.generics.span(), "a plugin cannot be generic" ) );
+  }
+  let self_path = type_path( &item.self_ty, "the plugin must be a named type" )?;
+  let self_ty = &item.self_ty;
+  let plugin_type = match &item.trait_ {
+    Some( (Some( bang ), _, _) ) => return Err( Error::new( bang.span(), "a plugin cannot be a negative impl" ) ),
+    Some( (None, path, _) ) => {
+      reject_type_argument( args, "the plugin type is the implemented trait, remove this argument" )?;
+      PluginType::Dyn( path.clone() )
+    }
+    None => {
+      reject_type_argument( args, "the plugin type is the type of the impl block, remove this argument" )?;
+      PluginType::Concrete( self_path.clone() )
+    }
+  };
+  Ok( Constructor {
+    plugin_type,
+    plugin: quote!( ::std::boxed::Box::new( <#self_ty as ::core::default::Default>::default() ) ),
+    name: last_ident( &self_path ),
+  })
+}
+
+// If `ty` is `Box<T>` return `T`
+fn boxed( ty: &Type ) -> Option<&Type> {
+  let Type::Path( TypePath { qself: None, path } ) = ty else { return None };
+  let last = path.segments.last()?;
+  let PathArguments::AngleBracketed( arguments ) = &last.arguments else { return None };
+  match arguments.args.first() {
+    Some( GenericArgument::Type( inner ) ) if last.ident == "Box" && arguments.args.len() == 1 => Some( inner ),
+    _ => None,
+  }
+}
+
+// `fn constructor() -> T` registers the returned value. The plugin type is the one in the attribute, or else the returned type
+fn fn_constructor( args: PluginArgs, item: &ItemFn ) -> syn::Result<(Constructor, PluginArgs)> {
+  let signature = &item.sig;
+  if !signature.generics.params.is_empty() {
+    return Err( Error::new( signature.generics.span(), "the constructor of a plugin cannot be generic" ) );
+  }
+  if let Some( asyncness ) = &signature.asyncness {
+    return Err( Error::new( asyncness.span(), "the constructor of a plugin cannot be async" ) );
+  }
+  if !signature.inputs.is_empty() {
+    return Err( Error::new( signature.inputs.span(), "the constructor of a plugin cannot take arguments" ) );
+  }
+  let ReturnType::Type( _, returned ) = &signature.output else {
+    return Err( Error::new( signature.span(), "the constructor of a plugin must return the plugin" ) );
+  };
+
+  let ident = &signature.ident;
+  let plugin = match boxed( returned ) {
+    Some( _ ) => quote!( #ident() ),
+    None => quote!( ::std::boxed::Box::new( #ident() ) ),
+  };
+  let PluginArgs { plugin_type, options } = args;
+  let plugin_type = match plugin_type {
+    Some( (plugin_type, _) ) => plugin_type,
+    None => match boxed( returned ).unwrap_or( returned ) {
+      Type::TraitObject( TypeTraitObject { bounds, .. } ) if bounds.len() == 1 => match bounds.first() {
+        Some( syn::TypeParamBound::Trait( bound ) ) => PluginType::Dyn( bound.path.clone() ),
+        _ => return Err( Error::new( returned.span(), "the plugin must be a trait object or a named type" ) ),
+      },
+      inner => PluginType::Concrete( type_path( inner, "the plugin must be a trait object or a named type" )? ),
+    },
+  };
+  Ok( (Constructor { plugin_type, plugin, name: ident.to_string() }, PluginArgs { plugin_type: None, options }) )
+}
+
+fn expand( args: PluginArgs, item: Item ) -> syn::Result<TokenStream2> {
+  let (constructor, args) = match &item {
+    Item::Impl( item ) => ( impl_constructor( &args, item )?, args ),
+    Item::Fn( item ) => fn_constructor( args, item )?,
+    _ => return Err( Error::new( Span::call_site(), "`#[plugin]` must be placed on an impl block or on a function returning the plugin" ) ),
+  };
+
+  let Constructor { plugin_type, plugin, name } = constructor;
+  let register = format_ident!( "__aanyx_register_{}", plugin_type.symbol() );
+  let try_register = format_ident!( "__aanyx_try_register_{}", plugin_type.symbol() );
+  let mut name = quote!( #name );
+  let mut static_feature = None;
+  let mut builders = Vec::new();
+  for PluginOption { key, value } in args.options {
+    if key == "name" {
+      name = value.into_token_stream();
+    } else if key == "static_feature" {
+      static_feature = Some( value );
+    } else {
+      let builder = format_ident!( "with_{}", key, span = key.span() );
+      builders.push( quote!( .#builder( #value ) ) );
+    }
+  }
+
+  let krate = quote!( ::aanyx );
+  let value = quote! {
+    ::aanyx::plugin::PluginDeclaration::new( #register, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") )
+      .with_try_register( #try_register ).with_metadata( ::aanyx::plugin_metadata!() ) #( #builders )*
+  };
+  let declaration = match static_feature {
+    None => declaration_static( &krate, &plugin_type, value ),
+    Some( feature ) => {
+      let dynamic = declaration_static( &krate, &plugin_type, value.clone() );
+      let linked = linked_declaration( &krate, &plugin_type, value );
+      quote! {
+        #[cfg(not(feature = #feature))]
+        const _: () = { #dynamic };
+        #[cfg(feature = #feature)]
+        #linked
+      }
+    }
+  };
+
+  Ok( quote! {
+    #item
+
+    #[doc(hidden)]
+    #[allow(improper_ctypes_definitions, non_snake_case)]
+    extern "C" fn #register( registrar: &mut dyn ::aanyx::plugin::PluginRegistrar<#plugin_type> ) {
+      let _ = registrar.register_plugin( #name, #plugin );
+    }
+
+    #[doc(hidden)]
+    #[allow(improper_ctypes_definitions, non_snake_case)]
+    extern "C" fn #try_register( registrar: &mut dyn ::aanyx::plugin::PluginRegistrar<#plugin_type> ) -> ::core::result::Result<(), ::std::string::String> {
+      ::aanyx::plugin::catch_panic( || { let _ = registrar.register_plugin( #name, #plugin ); } )
+    }
+
+    #declaration
+  })
+}
+
+// `$crate; plugin type; declaration expression`, used by `export_plugin!`
+struct ExportArgs {
+  krate: TokenStream2,
+  plugin_type: Type,
+  declaration: Expr,
+}
+
+impl Parse for ExportArgs {
+  fn parse( input: ParseStream ) -> syn::Result<Self> {
+    let krate = input.step( |cursor| cursor.token_tree().map( |(token, rest)| (token.into_token_stream(), rest) ).ok_or_else( || cursor.error( "expected the crate path" ) ) )?;
+    input.parse::<Token![;]>()?;
+    let plugin_type = input.parse()?;
+    input.parse::<Token![;]>()?;
+    let declaration = input.parse()?;
+    Ok( Self { krate, plugin_type, declaration } )
+  }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __export_declaration( input: TokenStream ) -> TokenStream {
+  let ExportArgs { krate, plugin_type, declaration } = syn::parse_macro_input!( input as ExportArgs );
+  declaration_static( &krate, &plugin_type, declaration.into_token_stream() ).into()
+}
+
+// The exported declaration of `plugin_type`, with a copy of its metadata in the `.aanyx_metadata` section,
+// see `aanyx::plugin::METADATA_SECTION`
+fn declaration_static( krate: &TokenStream2, plugin_type: &impl ToTokens, declaration: TokenStream2 ) -> TokenStream2 {
+  let name = symbol::declaration_symbol( plugin_type.to_token_stream() );
+  let symbol = format_ident!( "{}", name );
+  let value = format_ident!( "__aanyx_declaration_{}", name );
+  let metadata = format_ident!( "__aanyx_metadata_{}", name );
+  quote! {
+    #[doc(hidden)]
+    #[allow(non_upper_case_globals)]
+    const #value: #krate::plugin::PluginDeclaration<#plugin_type> = #declaration;
+
+    #[doc(hidden)]
+    #[no_mangle]
+    #[allow(non_upper_case_globals)]
+    pub static #symbol: #krate::plugin::PluginDeclaration<#plugin_type> = #value;
+
+    #[used]
+    #[cfg_attr(all(unix, not(target_vendor = "apple")), link_section = ".aanyx_metadata")]
+    #[allow(non_upper_case_globals)]
+    static #metadata: [u8; #krate::plugin::metadata_record_len( #name, &#value.metadata )] = #krate::plugin::metadata_record( #name, &#value.metadata );
+  }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __export_static_declaration( input: TokenStream ) -> TokenStream {
+  let ExportArgs { krate, plugin_type, declaration } = syn::parse_macro_input!( input as ExportArgs );
+  linked_declaration( &krate, &plugin_type, declaration.into_token_stream() ).into()
+}
+
+// The declaration of `plugin_type`, collected at link time by the `StaticPluginManager`
+fn linked_declaration( krate: &TokenStream2, plugin_type: &impl ToTokens, declaration: TokenStream2 ) -> TokenStream2 {
+  let symbol = proc_macro2::Literal::byte_string( symbol::declaration_symbol( plugin_type.to_token_stream() ).as_bytes() );
+  quote! {
+    const _: () = {
+      static DECLARATION: #krate::plugin::PluginDeclaration<#plugin_type> = #declaration;
+      #krate::plugin::plugin_inventory::submit!( #krate::plugin::StaticDeclaration::new( #symbol, &DECLARATION ) );
+    };
+  }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __plugin_symbol( input: TokenStream ) -> TokenStream {
+  let plugin_type = syn::parse_macro_input!( input as Type );
+  let symbol = proc_macro2::Literal::byte_string( symbol::declaration_symbol( plugin_type.to_token_stream() ).as_bytes() );
+  quote!( ( #symbol as &'static [u8] ) ).into()
+}
+
+// The signatures of the trait items, without attributes and default bodies, in canonical form
+fn interface_description( item: &ItemTrait ) -> String {
+  let ident = &item.ident;
+  let (_, generics, where_clause) = item.generics.split_for_impl();
+  let supertraits = &item.supertraits;
+  let mut description = symbol::canonical( quote!( trait #ident #generics : #supertraits #where_clause ) );
+  for item in &item.items {
+    let signature = match item {
+      // The names of the arguments don't matter
+      TraitItem::Fn( item ) => {
+        let Signature { constness, asyncness, unsafety, abi, ident, generics, inputs, variadic, output, .. } = &item.sig;
+        let where_clause = &generics.where_clause;
+        let inputs = inputs.iter().map( |input| match input {
+          FnArg::Receiver( receiver ) => receiver.to_token_stream(),
+          FnArg::Typed( argument ) => argument.ty.to_token_stream(),
+        });
+        quote!( #constness #asyncness #unsafety #abi fn #ident #generics ( #( #inputs ),* #variadic ) #output #where_clause )
+      }
+      TraitItem::Type( TraitItemType { ident, generics, bounds, .. } ) => quote!( type #ident #generics : #bounds ),
+      TraitItem::Const( TraitItemConst { ident, ty, .. } ) => quote!( const #ident : #ty ),
+      other => other.to_token_stream(),
+    };
+    description.push( ';' );
+    description.push_str( &symbol::canonical( signature ) );
+  }
+  description
+}
+
+/// Implement `Interface` for a trait object, with a fingerprint of its signatures. See the documentation of `aanyx::interface`.
+#[proc_macro_attribute]
+pub fn interface( args: TokenStream, item: TokenStream ) -> TokenStream {
+  if !args.is_empty() {
+    return Error::new( TokenStream2::from( args ).span(), "`#[interface]` doesn't take arguments" ).into_compile_error().into();
+  }
+  let item = syn::parse_macro_input!( item as ItemTrait );
+  let ident = &item.ident;
+  let (impl_generics, generics, where_clause) = item.generics.split_for_impl();
+  let description = interface_description( &item );
+  quote! {
+    #item
+
+    impl #impl_generics ::aanyx::plugin::Interface for dyn #ident #generics #where_clause {
+      const FINGERPRINT: u64 = ::aanyx::plugin::fingerprint( #description );
+    }
+  }.into()
+}
+
+/// Generate a `#[repr(C)]` vtable for a plugin trait. See the documentation of `aanyx::stable_abi`.
+#[proc_macro_attribute]
+pub fn stable_abi( args: TokenStream, item: TokenStream ) -> TokenStream {
+  if !args.is_empty() {
+    return Error::new( TokenStream2::from( args ).span(), "`#[stable_abi]` doesn't take arguments" ).into_compile_error().into();
+  }
+  let item = syn::parse_macro_input!( item as ItemTrait );
+  stable::expand( item ).unwrap_or_else( Error::into_compile_error ).into()
+}
+
+/// Export a plugin. See the documentation of `aanyx::plugin` for the details.
+#[proc_macro_attribute]
+pub fn plugin( args: TokenStream, item: TokenStream ) -> TokenStream {
+  let args = syn::parse_macro_input!( args as PluginArgs );
+  let item = syn::parse_macro_input!( item as Item );
+  expand( args, item ).unwrap_or_else( Error::into_compile_error ).into()
+}
@@ -0,0 +1,262 @@
+//! The `#[stable_abi]` attribute: a `#[repr(C)]` vtable of `extern "C"` functions for a plugin trait.
+//!
+//! For `trait Greeter` it generates:
+//! * `GreeterFfi`, a `#[repr(C)]` object made of a pointer to a boxed plugin and a pointer to the vtable. It implements
+//!   `Greeter` by calling the vtable, so the host uses it as any other plugin
+//! * the vtable and the `extern "C"` shims calling the methods of the plugin, converting the arguments from and to the
+//!   types of `aanyx::abi`
+//! * the implementation of `StableInterface` for `dyn Greeter`
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::spanned::Spanned;
+use syn::{Error, FnArg, Ident, ItemTrait, ReturnType, TraitItem, TraitItemFn, Type, TypePath, TypeReference};
+
+const PRIMITIVES: &[&str] = &["i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize", "f32", "f64", "bool"];
+
+// How a type crosses the boundary
+enum Kind {
+  // Primitives are passed as they are
+  Direct( Type ),
+  Str,
+  String,
+  Slice( Type ),
+}
+
+fn is_primitive( ty: &Type ) -> bool {
+  matches!( ty, Type::Path( TypePath { qself: None, path } ) if path.get_ident().is_some_and( |ident| PRIMITIVES.contains( &ident.to_string().as_str() ) ) )
+}
+
+fn is_ident( ty: &Type, name: &str ) -> bool {
+  matches!( ty, Type::Path( TypePath { qself: None, path } ) if path.is_ident( name ) )
+}
+
+fn unsupported( ty: &Type ) -> Error {
+  Error::new( ty.span(), format!( "`{}` cannot cross the stable ABI, use primitives, `&str`, `String` or slices of primitives", ty.to_token_stream() ) )
+}
+
+impl Kind {
+  fn of_argument( ty: &Type ) -> syn::Result<Self> {
+    match ty {
+      ty if is_primitive( ty ) => Ok( Self::Direct( ty.clone() ) ),
+      ty if is_ident( ty, "String" ) => Ok( Self::String ),
+      Type::Reference( TypeReference { mutability: None, elem, .. } ) => match elem.as_ref() {
+        elem if is_ident( elem, "str" ) => Ok( Self::Str ),
+        Type::Slice( slice ) if is_primitive( &slice.elem ) => Ok( Self::Slice( (*slice.elem).clone() ) ),
+        _ => Err( unsupported( ty ) ),
+      },
+      _ => Err( unsupported( ty ) ),
+    }
+  }
+
+  fn of_output( ty: &Type ) -> syn::Result<Self> {
+    match ty {
+      ty if is_primitive( ty ) => Ok( Self::Direct( ty.clone() ) ),
+      ty if is_ident( ty, "String" ) => Ok( Self::String ),
+      _ => Err( unsupported( ty ) ),
+    }
+  }
+
+  fn ffi_type( &self ) -> TokenStream {
+    match self {
+      Self::Direct( ty ) => ty.to_token_stream(),
+      Self::Str => quote!( ::aanyx::abi::RStr<'_> ),
+      Self::String => quote!( ::aanyx::abi::RString ),
+      Self::Slice( ty ) => quote!( ::aanyx::abi::RSlice<'_, #ty> ),
+    }
+  }
+
+  // Convert the standard `value` into its stable representation
+  fn to_ffi( &self, value: &Ident ) -> TokenStream {
+    match self {
+      Self::Direct( _ ) => quote!( #value ),
+      Self::Str => quote!( ::aanyx::abi::RStr::from( #value ) ),
+      Self::String => quote!( ::aanyx::abi::RString::from( #value ) ),
+      Self::Slice( _ ) => quote!( ::aanyx::abi::RSlice::from( #value ) ),
+    }
+  }
+
+  // Convert the stable `value` back into the standard type
+  fn to_standard( &self, value: &Ident ) -> TokenStream {
+    match self {
+      Self::Direct( _ ) => quote!( #value ),
+      Self::Str => quote!( #value.as_str() ),
+      Self::String => quote!( ::std::string::String::from( #value ) ),
+      Self::Slice( _ ) => quote!( #value.as_slice() ),
+    }
+  }
+}
+
+// The generated pieces of a method
+struct Method {
+  field: TokenStream,
+  shim: TokenStream,
+  implementation: TokenStream,
+}
+
+fn method( item: &TraitItemFn ) -> syn::Result<Method> {
+  let signature = &item.sig;
+  if !signature.generics.params.is_empty() || signature.generics.where_clause.is_some() {
+    return Err( Error::new( signature.generics.span(), "the methods of a stable interface cannot be generic" ) );
+  }
+  if let Some( token ) = signature.asyncness.as_ref().map( Spanned::span ).or( signature.constness.as_ref().map( Spanned::span ) ).or( signature.abi.as_ref().map( Spanned::span ) ) {
+    return Err( Error::new( token, "the methods of a stable interface cannot be async, const or extern" ) );
+  }
+  if let Some( variadic ) = &signature.variadic {
+    return Err( Error::new( variadic.span(), "the methods of a stable interface cannot be variadic" ) );
+  }
+
+  if signature.ident == "drop" {
+    return Err( Error::new( signature.ident.span(), "`drop` is reserved by the vtable of a stable interface" ) );
+  }
+  let mutable = match signature.inputs.first() {
+    Some( FnArg::Receiver( receiver ) ) if receiver.reference.is_some() && receiver.colon_token.is_none() => receiver.mutability.is_some(),
+    _ => return Err( Error::new( signature.span(), "the methods of a stable interface must take `&self` or `&mut self`" ) ),
+  };
+  let mut arguments = Vec::new();
+  for (index, input) in signature.inputs.iter().skip( 1 ).enumerate() {
+    let FnArg::Typed( argument ) = input else { unreachable!( "only the first argument can be a receiver" ) };
+    arguments.push( (format_ident!( "argument{}", index ), (*argument.ty).clone(), Kind::of_argument( &argument.ty )?) );
+  }
+  let output = match &signature.output {
+    ReturnType::Default => None,
+    ReturnType::Type( _, ty ) => Some( ((**ty).clone(), Kind::of_output( ty )?) ),
+  };
+
+  let name = &signature.ident;
+  let unsafety = &signature.unsafety;
+  let (data, pointer, this) = if mutable {
+    ( quote!( self.data ), quote!( *mut ::core::ffi::c_void ), quote!( &mut **( data as *mut ::std::boxed::Box<T> ) ) )
+  } else {
+    ( quote!( self.data as *const ::core::ffi::c_void ), quote!( *const ::core::ffi::c_void ), quote!( &**( data as *const ::std::boxed::Box<T> ) ) )
+  };
+  let receiver = if mutable { quote!( &mut self ) } else { quote!( &self ) };
+  let names: Vec<&Ident> = arguments.iter().map( |(name, _, _)| name ).collect();
+  let types: Vec<&Type> = arguments.iter().map( |(_, ty, _)| ty ).collect();
+  let ffi_types: Vec<TokenStream> = arguments.iter().map( |(_, _, kind)| kind.ffi_type() ).collect();
+  let to_ffi: Vec<TokenStream> = arguments.iter().map( |(name, _, kind)| kind.to_ffi( name ) ).collect();
+  let from_ffi: Vec<TokenStream> = arguments.iter().map( |(name, _, kind)| kind.to_standard( name ) ).collect();
+  let result = format_ident!( "result" );
+  let (output_type, ffi_output, output_to_ffi, output_from_ffi) = match &output {
+    None => ( quote!(), quote!(), quote!( #result ), quote!( #result ) ),
+    Some( (ty, kind) ) => {
+      let ffi_type = kind.ffi_type();
+      ( quote!( -> #ty ), quote!( -> #ffi_type ), kind.to_ffi( &result ), kind.to_standard( &result ) )
+    }
+  };
+
+  Ok( Method {
+    field: quote!( #name: unsafe extern "C" fn( #pointer #( , #ffi_types )* ) #ffi_output ),
+    shim: quote! {
+      unsafe extern "C" fn #name( data: #pointer #( , #names: #ffi_types )* ) #ffi_output {
+        let this = unsafe { #this };
+        let #result = unsafe { this.#name( #( #from_ffi ),* ) };
+        #output_to_ffi
+      }
+    },
+    implementation: quote! {
+      #unsafety fn #name( #receiver #( , #names: #types )* ) #output_type {
+        let #result = unsafe { ( self.vtable.#name )( #data #( , #to_ffi )* ) };
+        #output_from_ffi
+      }
+    },
+  })
+}
+
+pub fn expand( item: ItemTrait ) -> syn::Result<TokenStream> {
+  if !item.generics.params.is_empty() || item.generics.where_clause.is_some() {
+    return Err( Error::new( item.generics.span(), "a stable interface cannot be generic" ) );
+  }
+  if !item.supertraits.is_empty() {
+    return Err( Error::new( item.supertraits.span(), "a stable interface cannot have supertraits" ) );
+  }
+  if let Some( unsafety ) = &item.unsafety {
+    return Err( Error::new( unsafety.span(), "a stable interface cannot be an unsafe trait" ) );
+  }
+  let mut methods = Vec::new();
+  for trait_item in &item.items {
+    match trait_item {
+      TraitItem::Fn( trait_item ) => methods.push( method( trait_item )? ),
+      other => return Err( Error::new( other.span(), "a stable interface can only contain methods" ) ),
+    }
+  }
+
+  let vis = &item.vis;
+  let ident = &item.ident;
+  let ffi = format_ident!( "{}Ffi", ident );
+  let vtable = format_ident!( "__{}VTable", ident );
+  let shims = format_ident!( "__{}Shims", ident );
+  let names: Vec<&Ident> = item.items.iter().filter_map( |trait_item| match trait_item {
+    TraitItem::Fn( trait_item ) => Some( &trait_item.sig.ident ),
+    _ => None,
+  }).collect();
+  let fields = methods.iter().map( |method| &method.field );
+  let shim_fns = methods.iter().map( |method| &method.shim );
+  let implementations = methods.iter().map( |method| &method.implementation );
+  let ffi_doc = format!( "A `#[repr(C)]` object implementing [`{ident}`] through a vtable of `extern \"C\"` functions, so it can be shared by a host and a plugin compiled with different versions of rustc." );
+
+  Ok( quote! {
+    #item
+
+    #[doc(hidden)]
+    #[repr(C)]
+    #[allow(non_camel_case_types, non_snake_case)]
+    struct #vtable {
+      drop: unsafe extern "C" fn( *mut ::core::ffi::c_void ),
+      #( #fields, )*
+    }
+
+    #[doc = #ffi_doc]
+    #[repr(C)]
+    #vis struct #ffi {
+      data: *mut ::core::ffi::c_void,
+      vtable: &'static #vtable,
+    }
+
+    const _: () = {
+      struct #shims<T: ?Sized>( ::core::marker::PhantomData<*const T> );
+
+      #[allow(non_snake_case, unused_unsafe)]
+      impl<T: #ident + ?Sized + 'static> #shims<T> {
+        const VTABLE: #vtable = #vtable { drop: Self::drop, #( #names: Self::#names ),* };
+
+        unsafe extern "C" fn drop( data: *mut ::core::ffi::c_void ) {
+          ::core::mem::drop( unsafe { ::std::boxed::Box::from_raw( data as *mut ::std::boxed::Box<T> ) } );
+        }
+
+        #( #shim_fns )*
+      }
+
+      impl #ffi {
+        /// Wrap the plugin into the stable object
+        pub fn new<T: #ident + ?Sized + 'static>( plugin: ::std::boxed::Box<T> ) -> Self {
+          let data = ::std::boxed::Box::into_raw( ::std::boxed::Box::new( plugin ) ) as *mut ::core::ffi::c_void;
+          Self { data, vtable: &#shims::<T>::VTABLE }
+        }
+      }
+
+      #[allow(unused_unsafe)]
+      impl #ident for #ffi {
+        #( #implementations )*
+      }
+
+      impl ::core::ops::Drop for #ffi {
+        fn drop( &mut self ) {
+          unsafe { ( self.vtable.drop )( self.data ) };
+        }
+      }
+
+      unsafe impl ::aanyx::abi::StableInterface for dyn #ident {
+        type Ffi = #ffi;
+
+        fn into_ffi( plugin: ::std::boxed::Box<Self> ) -> #ffi {
+          #ffi::new( plugin )
+        }
+
+        fn from_ffi( ffi: #ffi ) -> ::std::boxed::Box<Self> {
+          ::std::boxed::Box::new( ffi )
+        }
+      }
+    };
+  })
+}
@@ -0,0 +1,100 @@
+//! The mangling of the plugin types into the names of their declarations.
+//!
+//! The type is first printed in a canonical form, which only depends on its tokens: a single space separates two
+//! consecutive identifiers (like in `dyn Plugin`) and nothing else is separated, while the leading `::` of the paths are dropped.
+//! Then the name is `plugin_declaration_`, followed by `dyn_` for trait objects, followed by the rest of the type:
+//! * unchanged if it's a single identifier, so `dyn Plugin` becomes `plugin_declaration_dyn_Plugin`
+//! * otherwise escaped, replacing every byte of the UTF-8 encoding that is not an ASCII letter or digit with `_` and its two hex digits,
+//!   so `dyn api::Handler<u8>` becomes `plugin_declaration_dyn_api_3a_3aHandler_3cu8_3e`
+//!
+//! An identifier containing `_` followed by two hex digits is escaped too, so `a_3a_3aB` doesn't become the same name
+//! of `a::B`, and so is a type that is not a trait object starting with `dyn_`, whose `d` is escaped so it doesn't look
+//! like a trait object. This way the escaped names always contain an escape and the unchanged ones never do, and the
+//! mangling can be reversed.
+//!
+//! The result is always a valid identifier, so it can be used both as the name of the static and as its symbol.
+
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+
+const PREFIX: &str = "plugin_declaration_";
+
+fn is_word( c: char ) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+// The keywords that can precede a path in a type
+const KEYWORDS: &[&str] = &["dyn", "impl", "as", "mut", "const", "for"];
+
+fn print( tokens: TokenStream, out: &mut String ) {
+  let mut skip_colon = false;
+  // Whether the previous token can be followed by `::` in the middle of a path
+  let mut in_path = false;
+  for token in tokens {
+    match token {
+      TokenTree::Group( group ) => {
+        let (open, close) = match group.delimiter() {
+          Delimiter::Parenthesis => ("(", ")"),
+          Delimiter::Brace => ("{", "}"),
+          Delimiter::Bracket => ("[", "]"),
+          Delimiter::None => ("", ""),
+        };
+        out.push_str( open );
+        print( group.stream(), out );
+        out.push_str( close );
+        in_path = false;
+      }
+      // A `::` at the beginning of a path is dropped
+      TokenTree::Punct( punct ) if punct.as_char() == ':' && skip_colon => skip_colon = false,
+      TokenTree::Punct( punct ) if punct.as_char() == ':' && punct.spacing() == Spacing::Joint && !in_path => skip_colon = true,
+      TokenTree::Punct( punct ) => {
+        out.push( punct.as_char() );
+        in_path = matches!( punct.as_char(), '>' | ':' );
+      }
+      word => {
+        if out.ends_with( is_word ) {
+          out.push( ' ' );
+        }
+        let word = word.to_string();
+        in_path = !KEYWORDS.contains( &word.as_str() );
+        out.push_str( &word );
+      }
+    }
+  }
+}
+
+/// The canonical form of the type, also used for the signatures of the interfaces
+pub fn canonical( ty: TokenStream ) -> String {
+  let mut out = String::new();
+  print( ty, &mut out );
+  out
+}
+
+// Whether `name` contains `_` followed by two lowercase hex digits, which would be read as an escape
+fn has_escape( name: &str ) -> bool {
+  name.as_bytes().windows( 3 ).any( |window| window[0] == b'_' && window[1..].iter().all( |c| matches!( c, b'0'..=b'9' | b'a'..=b'f' ) ) )
+}
+
+// Every byte of the UTF-8 encoding is escaped on its own, so that each escape has exactly two digits
+fn escape( name: &str ) -> String {
+  name.bytes().map( |c| if c.is_ascii_alphanumeric() { char::from( c ).to_string() } else { format!( "_{c:02x}" ) } ).collect()
+}
+
+/// The name of the declaration exporting the plugin type `ty`
+pub fn declaration_symbol( ty: TokenStream ) -> String {
+  let canonical = canonical( ty );
+  let (kind, rest) = match canonical.strip_prefix( "dyn " ) {
+    Some( rest ) => ("dyn_", rest),
+    None => ("", canonical.as_str()),
+  };
+  let is_ident = rest.chars().next().is_some_and( |c| !c.is_ascii_digit() ) && rest.chars().all( is_word );
+  let rest = if is_ident && !has_escape( rest ) && !(kind.is_empty() && rest.starts_with( "dyn_" )) {
+    rest.to_string()
+  } else {
+    let escaped = escape( rest );
+    match escaped.strip_prefix( 'd' ) {
+      Some( tail ) if kind.is_empty() && escaped.starts_with( "dyn_" ) => format!( "_64{tail}" ),
+      _ => escaped,
+    }
+  };
+  format!( "{PREFIX}{kind}{rest}" )
+}
@@ -0,0 +1,33 @@
+//! The interface shared between the integration tests (the host) and the fixture plugins.
+
+#[aanyx::interface]
+pub trait Greeter {
+  fn greet( &self, name: &str ) -> String;
+}
+
+pub trait Counter {
+  /// Increment the counter and return its new value
+  fn increment( &self ) -> u64;
+}
+
+pub trait Handler<Request> {
+  fn handle( &self, request: Request ) -> String;
+}
+
+pub mod v2 {
+  pub trait Greeter {
+    fn greet( &self, name: &str, times: usize ) -> String;
+  }
+}
+
+#[aanyx::stable_abi]
+pub trait Calculator {
+  fn add( &self, a: i64, b: i64 ) -> i64;
+  fn sum( &self, values: &[f64] ) -> f64;
+  fn describe( &self, name: &str ) -> String;
+  fn rename( &mut self, name: String );
+}
+
+/// A service offered by the host to collect what the plugins do while they register
+#[derive(Default)]
+pub struct Events( pub std::sync::Mutex<Vec<String>> );
@@ -0,0 +1,11 @@
+//! Exports its plugin with the `#[aanyx::plugin]` attribute instead of `export_plugin!`.
+
+use plugin_test_api::Greeter;
+
+#[derive(Default)]
+struct Attribute;
+
+#[aanyx::plugin( name = "attribute" )]
+impl Greeter for Attribute {
+  fn greet( &self, name: &str ) -> String { format!("Greetings {name}") }
+}
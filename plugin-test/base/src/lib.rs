@@ -0,0 +1,28 @@
+//! A plugin other plugins depend on. It exports the same plugins with several declarations, to test different dependency graphs.
+
+use aanyx::plugin::{PluginDependency, PluginRegistrar};
+use aanyx::export_plugin;
+use plugin_test_api::Greeter;
+
+struct Base;
+impl Greeter for Base {
+  fn greet( &self, name: &str ) -> String { format!("Welcome {name}") }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let _ = registrar.register_plugin( "base", Box::new( Base ) );
+}
+
+export_plugin!( register, dyn Greeter );
+
+// `plugin-test-dependent` depends on this plugin, so they form a cycle
+type Cyclic = dyn Greeter;
+export_plugin!( register, Cyclic, dependencies = &[ PluginDependency::new( "plugin-test-dependent", "*" ) ] );
+
+type Outdated = dyn Greeter;
+export_plugin!( register, Outdated );
+
+// Rebuilt by `plugin-test-duplicate`, whose build depends on `plugin-test-dependent`
+type Rebuilt = dyn Greeter;
+export_plugin!( register, Rebuilt );
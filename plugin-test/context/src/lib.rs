@@ -0,0 +1,24 @@
+//! Uses the services of the host context while registering.
+
+use aanyx::{export_plugin, plugin::PluginRegistrar};
+use plugin_test_api::{Events, Greeter};
+
+struct Contextual {
+  greeting: String,
+}
+
+impl Greeter for Contextual {
+  fn greet( &self, name: &str ) -> String { format!("{} {name}", self.greeting) }
+}
+
+fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let context = registrar.context();
+  // SAFETY: `String` comes from std and `Events` from the api crate, both shared with the host in the same version
+  let greeting = unsafe { context.service::<String>( "greeting" ) }.cloned().unwrap_or_else( || String::from("Hi") );
+  if let Some( events ) = unsafe { context.service::<Events>( "events" ) } {
+    events.0.lock().unwrap().push( format!( "registering with {greeting}" ) );
+  }
+  let _ = registrar.register_plugin( "contextual", Box::new( Contextual { greeting } ) );
+}
+
+export_plugin!( register, dyn Greeter );
@@ -0,0 +1,40 @@
+//! The next build of the counter, which stores the value as a little endian u64 and migrates the state of the first build.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use aanyx::export_plugin;
+use aanyx::plugin::{PluginRegistrar, PluginState};
+use plugin_test_api::Counter;
+
+static VALUE: AtomicU64 = AtomicU64::new( 0 );
+
+struct LargeCounter;
+
+impl Counter for LargeCounter {
+  fn increment( &self ) -> u64 {
+    VALUE.fetch_add( 1, Ordering::SeqCst ) + 1
+  }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Counter> ) {
+  let _ = registrar.register_plugin( "counter", Box::new( LargeCounter ) );
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn export_state() -> Vec<u8> {
+  VALUE.load( Ordering::SeqCst ).to_le_bytes().to_vec()
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn import_state( state: PluginState ) -> Result<(), String> {
+  let value = match state.version {
+    1 => u32::from_le_bytes( state.data.try_into().map_err( |_| "invalid state".to_string() )? ) as u64,
+    2 => u64::from_le_bytes( state.data.try_into().map_err( |_| "invalid state".to_string() )? ),
+    version => return Err( format!( "unknown state version {version}" ) ),
+  };
+  VALUE.store( value, Ordering::SeqCst );
+  Ok(())
+}
+
+export_plugin!( register, dyn Counter, state_version = 2, export_state = export_state, import_state = import_state );
@@ -0,0 +1,39 @@
+//! A counter that keeps its value across reloads. Its state is the value as a little endian u32.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use aanyx::export_plugin;
+use aanyx::plugin::{PluginRegistrar, PluginState};
+use plugin_test_api::Counter;
+
+static VALUE: AtomicU32 = AtomicU32::new( 0 );
+
+struct SmallCounter;
+
+impl Counter for SmallCounter {
+  fn increment( &self ) -> u64 {
+    ( VALUE.fetch_add( 1, Ordering::SeqCst ) + 1 ) as u64
+  }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Counter> ) {
+  let _ = registrar.register_plugin( "counter", Box::new( SmallCounter ) );
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn export_state() -> Vec<u8> {
+  VALUE.load( Ordering::SeqCst ).to_le_bytes().to_vec()
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn import_state( state: PluginState ) -> Result<(), String> {
+  if state.version != 1 {
+    return Err( format!( "unknown state version {}", state.version ) );
+  }
+  let value = state.data.try_into().map_err( |_| "invalid state".to_string() )?;
+  VALUE.store( u32::from_le_bytes( value ), Ordering::SeqCst );
+  Ok(())
+}
+
+export_plugin!( register, dyn Counter, state_version = 1, export_state = export_state, import_state = import_state );
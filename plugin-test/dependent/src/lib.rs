@@ -0,0 +1,27 @@
+//! A plugin depending on `plugin-test-base`.
+
+use aanyx::plugin::{PluginDependency, PluginRegistrar};
+use aanyx::export_plugin;
+use plugin_test_api::Greeter;
+
+struct Dependent;
+impl Greeter for Dependent {
+  fn greet( &self, name: &str ) -> String { format!("Welcome back {name}") }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let _ = registrar.register_plugin( "dependent", Box::new( Dependent ) );
+}
+
+export_plugin!( register, dyn Greeter, dependencies = &[ PluginDependency::new( "plugin-test-base", "^0.1" ) ] );
+
+type Cyclic = dyn Greeter;
+export_plugin!( register, Cyclic, dependencies = &[ PluginDependency::new( "plugin-test-base", "*" ) ] );
+
+// Requires a version of `plugin-test-base` that doesn't exist
+type Outdated = dyn Greeter;
+export_plugin!( register, Outdated, dependencies = &[ PluginDependency::new( "plugin-test-base", "^2" ) ] );
+
+type Rebuilt = dyn Greeter;
+export_plugin!( register, Rebuilt, dependencies = &[ PluginDependency::new( "plugin-test-base", "*" ) ] );
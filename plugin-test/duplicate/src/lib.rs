@@ -0,0 +1,38 @@
+//! Registers a name twice and a name that is also registered by the greeter plugin.
+//! It also exports a build of `plugin-test-base` depending on `plugin-test-dependent`, which depends on `plugin-test-base`.
+
+use aanyx::export_plugin;
+use aanyx::plugin::{PluginDependency, PluginRegistrar};
+use plugin_test_api::Greeter;
+
+struct Texan;
+impl Greeter for Texan {
+  fn greet( &self, name: &str ) -> String { format!("Howdy {name}") }
+}
+
+struct First;
+impl Greeter for First {
+  fn greet( &self, name: &str ) -> String { format!("First {name}") }
+}
+
+struct Second;
+impl Greeter for Second {
+  fn greet( &self, name: &str ) -> String { format!("Second {name}") }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let _ = registrar.register_plugin( "english", Box::new( Texan ) );
+  let _ = registrar.register_plugin( "twin", Box::new( First ) );
+  let _ = registrar.register_plugin( "twin", Box::new( Second ) );
+}
+
+export_plugin!( register, dyn Greeter );
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register_rebuilt( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let _ = registrar.register_plugin( "rebuilt", Box::new( Texan ) );
+}
+
+type Rebuilt = dyn Greeter;
+export_plugin!( register_rebuilt, Rebuilt, id = "plugin-test-base", dependencies = &[ PluginDependency::new( "plugin-test-dependent", "*" ) ] );
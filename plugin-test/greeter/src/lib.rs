@@ -0,0 +1,42 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use aanyx::{export_plugin, plugin_metadata, abi::RStr, plugin::{Interface, PluginRegistrar}};
+use plugin_test_api::Greeter;
+
+struct English;
+impl Greeter for English {
+  fn greet( &self, name: &str ) -> String { format!("Hello {name}") }
+}
+
+struct Italian;
+impl Greeter for Italian {
+  fn greet( &self, name: &str ) -> String { format!("Ciao {name}") }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  log( "register" );
+  let _ = registrar.register_plugin( "english", Box::new( English ) );
+  let _ = registrar.register_plugin( "italian", Box::new( Italian ) );
+}
+
+// The lifecycle hooks and `register` append their name to the file in the `AANYX_LIFECYCLE_LOG` environment variable, if any
+fn log( event: &str ) {
+  if let Some( path ) = std::env::var_os( "AANYX_LIFECYCLE_LOG" ) {
+    let mut log = OpenOptions::new().create( true ).append( true ).open( path ).unwrap();
+    writeln!( log, "{event}" ).unwrap();
+  }
+}
+
+extern "C" fn init() { log( "init" ) }
+extern "C" fn shutdown() { log( "shutdown" ) }
+extern "C" fn before_reload() { log( "before_reload" ) }
+
+export_plugin!( register, dyn Greeter,
+  init = init,
+  shutdown = shutdown,
+  before_reload = before_reload,
+  fingerprint = <dyn Greeter as Interface>::FINGERPRINT,
+  metadata = plugin_metadata!().with_name( "Greeter" ).with_capabilities( &[RStr::new( "english" ), RStr::new( "italian" )] ),
+);
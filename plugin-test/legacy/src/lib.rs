@@ -0,0 +1,20 @@
+//! Compiled against an older `Greeter`, with the same name but another signature.
+
+use aanyx::{export_plugin, plugin::{Interface, PluginRegistrar}};
+
+#[aanyx::interface]
+pub trait Greeter {
+  fn greet( &self ) -> String;
+}
+
+struct Legacy;
+impl Greeter for Legacy {
+  fn greet( &self ) -> String { String::from("Hello stranger") }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let _ = registrar.register_plugin( "legacy", Box::new( Legacy ) );
+}
+
+export_plugin!( register, dyn Greeter, fingerprint = <dyn Greeter as Interface>::FINGERPRINT );
@@ -0,0 +1,73 @@
+//! Plugins linked into the binary of the host: a library with a dependent, one panicking and one with a taken name.
+
+use std::sync::Mutex;
+
+use aanyx::{export_static_plugin, plugin::{PluginDependency, PluginRegistrar}};
+use plugin_test_api::Greeter;
+
+static EVENTS: Mutex<Vec<String>> = Mutex::new( Vec::new() );
+
+/// The lifecycle hooks called by the host, in order
+pub fn events() -> Vec<String> {
+  EVENTS.lock().unwrap().clone()
+}
+
+fn log( event: &str ) {
+  EVENTS.lock().unwrap().push( event.to_string() );
+}
+
+struct English;
+impl Greeter for English {
+  fn greet( &self, name: &str ) -> String { format!("Hello {name}") }
+}
+
+struct Italian;
+impl Greeter for Italian {
+  fn greet( &self, name: &str ) -> String { format!("Ciao {name}") }
+}
+
+mod base {
+  use super::*;
+
+  fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+    log( "register base" );
+    let _ = registrar.register_plugin( "static-english", Box::new( English ) );
+  }
+  extern "C" fn init() { log( "init base" ) }
+  extern "C" fn shutdown() { log( "shutdown base" ) }
+
+  export_static_plugin!( register, dyn Greeter, id = "linked-base", version = "1.2.0", init = init, shutdown = shutdown );
+}
+
+// Sorted before its dependency
+mod app {
+  use super::*;
+
+  fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+    log( "register app" );
+    let _ = registrar.register_plugin( "static-italian", Box::new( Italian ) );
+  }
+  extern "C" fn shutdown() { log( "shutdown app" ) }
+
+  export_static_plugin!( register, dyn Greeter, id = "linked-app", shutdown = shutdown, dependencies = &[PluginDependency::new( "linked-base", "^1" )] );
+}
+
+mod panicking {
+  use super::*;
+
+  fn register( _: &mut dyn PluginRegistrar<dyn Greeter> ) {
+    panic!( "static panic" );
+  }
+
+  export_static_plugin!( register, dyn Greeter, id = "linked-panicking" );
+}
+
+mod twin {
+  use super::*;
+
+  fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+    let _ = registrar.register_plugin( "static-english", Box::new( Italian ) );
+  }
+
+  export_static_plugin!( register, dyn Greeter, id = "linked-twin" );
+}
@@ -0,0 +1,41 @@
+//! Declarations written by hand to simulate plugins compiled with other versions of rustc or aanyx.
+
+#![allow(non_upper_case_globals)]
+
+use aanyx::abi::RStr;
+use aanyx::plugin::{PluginDeclaration, PluginRegistrar};
+use plugin_test_api::Greeter;
+
+struct Old;
+impl Greeter for Old {
+  fn greet( &self, name: &str ) -> String { format!("Good morrow {name}") }
+}
+
+// The host must never call this function, because rustc versions are never compatible
+#[allow(improper_ctypes_definitions)]
+extern "C" fn abort( _registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  std::process::abort();
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let _ = registrar.register_plugin( "old", Box::new( Old ) );
+}
+
+#[no_mangle]
+pub static plugin_declaration_OtherRustc: PluginDeclaration<dyn Greeter> = PluginDeclaration {
+  rustc_version: RStr::new( "1.0.0" ),
+  ..PluginDeclaration::new( abort, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") )
+};
+
+#[no_mangle]
+pub static plugin_declaration_OtherCore: PluginDeclaration<dyn Greeter> = PluginDeclaration {
+  nyx_version: RStr::new( "0.0.1" ),
+  ..PluginDeclaration::new( register, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") )
+};
+
+#[no_mangle]
+pub static plugin_declaration_OtherLayout: PluginDeclaration<dyn Greeter> = PluginDeclaration {
+  declaration_version: 0,
+  ..PluginDeclaration::new( abort, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") )
+};
@@ -0,0 +1,16 @@
+//! Panics while registering its plugins, after registering the first one.
+
+use aanyx::{export_plugin, plugin::PluginRegistrar};
+use plugin_test_api::Greeter;
+
+struct Early;
+impl Greeter for Early {
+  fn greet( &self, name: &str ) -> String { format!("Hello {name}") }
+}
+
+fn register( registrar: &mut dyn PluginRegistrar<dyn Greeter> ) {
+  let _ = registrar.register_plugin( "early", Box::new( Early ) );
+  panic!( "cannot register {}", "late" );
+}
+
+export_plugin!( register, dyn Greeter );
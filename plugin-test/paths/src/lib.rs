@@ -0,0 +1,37 @@
+//! Exports plugin types written as paths and with generic arguments.
+
+use aanyx::{export_plugin, plugin::PluginRegistrar};
+
+struct Repeat;
+impl plugin_test_api::v2::Greeter for Repeat {
+  fn greet( &self, name: &str, times: usize ) -> String { format!("Hello {}", name.repeat( times )) }
+}
+
+struct Number;
+impl plugin_test_api::Handler<u32> for Number {
+  fn handle( &self, request: u32 ) -> String { format!("number {request}") }
+}
+
+struct Text;
+impl plugin_test_api::Handler<String> for Text {
+  fn handle( &self, request: String ) -> String { format!("text {request}") }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register_greeter( registrar: &mut dyn PluginRegistrar<dyn plugin_test_api::v2::Greeter> ) {
+  let _ = registrar.register_plugin( "repeat", Box::new( Repeat ) );
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register_number( registrar: &mut dyn PluginRegistrar<dyn plugin_test_api::Handler<u32>> ) {
+  let _ = registrar.register_plugin( "number", Box::new( Number ) );
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register_text( registrar: &mut dyn PluginRegistrar<dyn plugin_test_api::Handler<String>> ) {
+  let _ = registrar.register_plugin( "text", Box::new( Text ) );
+}
+
+export_plugin!( register_greeter, dyn plugin_test_api::v2::Greeter );
+export_plugin!( register_number, dyn plugin_test_api::Handler<u32> );
+export_plugin!( register_text, dyn plugin_test_api::Handler<String> );
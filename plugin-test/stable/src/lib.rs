@@ -0,0 +1,42 @@
+//! Exports a plugin through a stable interface, also with declarations simulating another version of rustc and a panic.
+
+#![allow(non_upper_case_globals)]
+
+use aanyx::{export_plugin, abi::RStr, plugin::{PluginDeclaration, PluginRegistrar}};
+use plugin_test_api::Calculator;
+
+struct Named {
+  name: String,
+}
+
+impl Calculator for Named {
+  fn add( &self, a: i64, b: i64 ) -> i64 { a + b }
+  fn sum( &self, values: &[f64] ) -> f64 { values.iter().sum() }
+  fn describe( &self, name: &str ) -> String { format!("{name} is {}", self.name) }
+  fn rename( &mut self, name: String ) { self.name = name }
+}
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Calculator> ) {
+  let _ = registrar.register_plugin( "calculator", Box::new( Named { name: String::from("a calculator") } ) );
+}
+
+export_plugin!( register, dyn Calculator, stable_abi = true );
+
+#[no_mangle]
+pub static plugin_declaration_OtherRustc: PluginDeclaration<dyn Calculator> = PluginDeclaration {
+  rustc_version: RStr::new( "1.0.0" ),
+  ..PluginDeclaration::new( register, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") ).with_stable_abi( true )
+};
+
+#[allow(improper_ctypes_definitions)]
+extern "C" fn try_register_panicking( registrar: &mut dyn PluginRegistrar<dyn Calculator> ) -> Result<(), String> {
+  aanyx::plugin::catch_panic( || {
+    register( registrar );
+    panic!( "the calculator is broken" );
+  })
+}
+
+#[no_mangle]
+pub static plugin_declaration_Panicking: PluginDeclaration<dyn Calculator> =
+  PluginDeclaration::new( register, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") ).with_stable_abi( true ).with_try_register( try_register_panicking );
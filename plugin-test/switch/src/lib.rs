@@ -0,0 +1,20 @@
+//! Exported from a shared library, or linked statically with the `static` feature.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use aanyx::{export_plugin, plugin::PluginRegistrar};
+use plugin_test_api::Counter;
+
+struct Switch {
+  count: AtomicU64,
+}
+
+impl Counter for Switch {
+  fn increment( &self ) -> u64 { self.count.fetch_add( 1, Ordering::SeqCst ) + 1 }
+}
+
+fn register( registrar: &mut dyn PluginRegistrar<dyn Counter> ) {
+  let _ = registrar.register_plugin( "switch", Box::new( Switch { count: AtomicU64::new( 0 ) } ) );
+}
+
+export_plugin!( register, dyn Counter, static_feature = "static", id = "switch" );
@@ -0,0 +1,231 @@
+//! Types with a stable layout, used by the interfaces generated with [`stable_abi`](crate::stable_abi).
+//!
+//! The layout of `&str`, `String` and `&[T]` is decided by the compiler, so they cannot be passed between a host and a
+//! plugin built by different versions of rustc. [`RStr`], [`RString`] and [`RSlice`] are `#[repr(C)]` replacements that
+//! can be converted from and to the standard types.
+//! ```
+//! use aanyx::abi::{RSlice, RStr, RString};
+//!
+//! let name = RStr::from( "Alice" );
+//! assert_eq!( name.as_str(), "Alice" );
+//!
+//! let greeting = RString::from( format!( "Hello {}", name.as_str() ) );
+//! assert_eq!( String::from( greeting ), "Hello Alice" );
+//!
+//! let values = RSlice::from( &[1.0, 2.5][..] );
+//! assert_eq!( values.as_slice().iter().sum::<f64>(), 3.5 );
+//! ```
+
+use std::ffi::c_void;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+
+use crate::plugin::RegisterError;
+
+/// A borrowed string slice with a stable layout
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RStr<'a> {
+  ptr: *const u8,
+  len: usize,
+  _marker: PhantomData<&'a str>,
+}
+
+// Like a `&str`, it can be shared between threads
+unsafe impl Send for RStr<'_> {}
+unsafe impl Sync for RStr<'_> {}
+
+impl<'a> RStr<'a> {
+  /// The same of `From`, usable in constants
+  pub const fn new( value: &'a str ) -> Self {
+    Self { ptr: value.as_ptr(), len: value.len(), _marker: PhantomData }
+  }
+
+  pub const fn as_str( &self ) -> &'a str {
+    // Built from a `&'a str` in `new`, so the bytes are valid UTF-8 for `'a`
+    unsafe { std::str::from_utf8_unchecked( std::slice::from_raw_parts( self.ptr, self.len ) ) }
+  }
+}
+
+impl<'a> From<&'a str> for RStr<'a> {
+  fn from( value: &'a str ) -> Self {
+    Self::new( value )
+  }
+}
+
+impl PartialEq for RStr<'_> {
+  fn eq( &self, other: &Self ) -> bool {
+    self.as_str() == other.as_str()
+  }
+}
+
+impl Eq for RStr<'_> {}
+
+impl fmt::Debug for RStr<'_> {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    fmt::Debug::fmt( self.as_str(), f )
+  }
+}
+
+impl fmt::Display for RStr<'_> {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    fmt::Display::fmt( self.as_str(), f )
+  }
+}
+
+/// A borrowed slice with a stable layout. `T` should have a stable layout too.
+#[repr(C)]
+pub struct RSlice<'a, T> {
+  ptr: *const T,
+  len: usize,
+  _marker: PhantomData<&'a [T]>,
+}
+
+impl<T> Clone for RSlice<'_, T> {
+  fn clone( &self ) -> Self {
+    *self
+  }
+}
+
+impl<T> Copy for RSlice<'_, T> {}
+
+// Like a `&[T]`, it can be shared between threads if `T` can
+unsafe impl<T: Sync> Send for RSlice<'_, T> {}
+unsafe impl<T: Sync> Sync for RSlice<'_, T> {}
+
+impl<'a, T> RSlice<'a, T> {
+  /// The same of `From`, usable in constants
+  pub const fn new( value: &'a [T] ) -> Self {
+    Self { ptr: value.as_ptr(), len: value.len(), _marker: PhantomData }
+  }
+
+  pub const fn as_slice( &self ) -> &'a [T] {
+    // Built from a `&'a [T]` in `new`
+    unsafe { std::slice::from_raw_parts( self.ptr, self.len ) }
+  }
+}
+
+impl<'a, T> From<&'a [T]> for RSlice<'a, T> {
+  fn from( value: &'a [T] ) -> Self {
+    Self::new( value )
+  }
+}
+
+impl<T: PartialEq> PartialEq for RSlice<'_, T> {
+  fn eq( &self, other: &Self ) -> bool {
+    self.as_slice() == other.as_slice()
+  }
+}
+
+impl<T: Eq> Eq for RSlice<'_, T> {}
+
+impl<T: fmt::Debug> fmt::Debug for RSlice<'_, T> {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    fmt::Debug::fmt( self.as_slice(), f )
+  }
+}
+
+/// An owned string with a stable layout. The memory is released by the side that allocated it,
+/// so a string created by a plugin can be dropped by the host.
+#[repr(C)]
+pub struct RString {
+  ptr: *mut u8,
+  len: usize,
+  capacity: usize,
+  drop: unsafe extern "C" fn( *mut u8, usize, usize ),
+}
+
+unsafe extern "C" fn drop_string( ptr: *mut u8, len: usize, capacity: usize ) {
+  drop( unsafe { String::from_raw_parts( ptr, len, capacity ) } );
+}
+
+impl RString {
+  pub fn as_str( &self ) -> &str {
+    // Built from a `String` in `From`
+    unsafe { std::str::from_utf8_unchecked( std::slice::from_raw_parts( self.ptr, self.len ) ) }
+  }
+}
+
+impl From<String> for RString {
+  fn from( value: String ) -> Self {
+    let mut value = ManuallyDrop::new( value );
+    Self { ptr: value.as_mut_ptr(), len: value.len(), capacity: value.capacity(), drop: drop_string }
+  }
+}
+
+impl From<RString> for String {
+  /// The content is copied, because the `String` must be allocated by the side that will drop it
+  fn from( value: RString ) -> Self {
+    value.as_str().to_string()
+  }
+}
+
+impl Drop for RString {
+  fn drop( &mut self ) {
+    unsafe { (self.drop)( self.ptr, self.len, self.capacity ) };
+  }
+}
+
+impl fmt::Debug for RString {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    fmt::Debug::fmt( self.as_str(), f )
+  }
+}
+
+impl fmt::Display for RString {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    fmt::Display::fmt( self.as_str(), f )
+  }
+}
+
+/// A trait object that can cross the boundary between host and plugin through a `#[repr(C)]` object made of
+/// a pointer to the plugin and a vtable of `extern "C"` functions. It's implemented by [`stable_abi`](crate::stable_abi).
+///
+/// ## Safety
+/// `Ffi` must have a stable layout and must not depend on the layout of any type decided by the compiler.
+pub unsafe trait StableInterface {
+  /// The `#[repr(C)]` object, which also implements the trait
+  type Ffi;
+
+  /// Wrap the plugin into the stable object. Called by the plugin.
+  fn into_ffi( plugin: Box<Self> ) -> Self::Ffi;
+
+  /// Use the stable object as a trait object. Called by the host.
+  fn from_ffi( ffi: Self::Ffi ) -> Box<Self>;
+}
+
+/// The registrar passed by the host to the plugins that export a stable interface.
+/// The plugins don't use it directly: their `register` function receives a [`PluginRegistrar`](crate::plugin::PluginRegistrar)
+/// that forwards the plugins to this one.
+#[repr(C)]
+pub struct StableRegistrar {
+  registrar: *mut c_void,
+  // Takes ownership of the `Ffi` object pointed by the last argument, returns false if the name is taken
+  register: unsafe extern "C" fn( *mut c_void, RStr<'_>, *mut c_void ) -> bool,
+  // Receives the message of a panic caught while registering the plugins
+  panicked: unsafe extern "C" fn( *mut c_void, RStr<'_> ),
+}
+
+impl StableRegistrar {
+  /// ## Safety
+  /// `register` and `panicked` are called with `registrar` as their first argument every time a plugin uses this registrar,
+  /// so they must accept it: `registrar` must point to the value they expect and stay valid as long as this registrar is used.
+  /// `register` must take ownership of the `Ffi` object pointed by its last argument, whose type depends on the plugin type.
+  #[doc(hidden)]
+  pub unsafe fn new( registrar: *mut c_void, register: unsafe extern "C" fn( *mut c_void, RStr<'_>, *mut c_void ) -> bool, panicked: unsafe extern "C" fn( *mut c_void, RStr<'_> ) ) -> Self {
+    Self { registrar, register, panicked }
+  }
+
+  /// Tell the host that registering the plugins panicked, so that the library is rejected
+  pub fn report_panic( &mut self, message: &str ) {
+    unsafe { (self.panicked)( self.registrar, RStr::from( message ) ) };
+  }
+
+  /// Hand the `plugin` to the host
+  pub fn register_plugin<PluginType: StableInterface + ?Sized>( &mut self, name: &str, plugin: Box<PluginType> ) -> Result<(), RegisterError> {
+    let mut ffi = ManuallyDrop::new( PluginType::into_ffi( plugin ) );
+    let registered = unsafe { (self.register)( self.registrar, RStr::from( name ), &mut *ffi as *mut PluginType::Ffi as *mut c_void ) };
+    if registered { Ok(()) } else { Err( RegisterError::NameTaken( name.to_string() ) ) }
+  }
+}
@@ -9,6 +9,32 @@ use std::alloc::System;
 #[global_allocator]
 static ALLOCATOR: System = System;
 
+/// A ready-made plugin manager that loads plugins from shared libraries using `libloading`.
+pub mod dylib;
+pub use dylib::{Collision, CollisionPolicy, Dylib, DylibLoader, DylibPluginManager, LoadError, UnloadError};
+
+/// A plugin manager for plugins linked into the binary.
+pub mod static_plugins;
+pub use static_plugins::StaticPluginManager;
+
+mod dependencies;
+
+/// Policies used to decide if a plugin compiled against another version of aanyx can be loaded.
+pub mod compatibility;
+pub use compatibility::{CompatibilityPolicy, ExactVersion, SemverCompatible};
+
+/// Load all the plugins found in a directory.
+pub mod discovery;
+pub use discovery::{discover, Discovered, DiscoveryError};
+
+/// Read the plugins offered by a library without opening it.
+pub mod inspect;
+pub use inspect::{inspect, Inspection, InspectedDeclaration, InspectError};
+
+/// Reload the plugins when their files change.
+pub mod watcher;
+pub use watcher::PluginWatcher;
+
 pub trait PluginLoader<PluginId, PluginType> {
   fn into_plugin( self ) -> (PluginId, PluginType);
 }
@@ -66,6 +92,13 @@ pub trait PluginLoader<PluginId, PluginType> {
 pub trait PluginManagerLoad<PluginId, PluginType, ManagerLoadError> 
 {
   fn load( &mut self, new_plugin: impl PluginLoader<PluginId, PluginType>) -> Result<(), ManagerLoadError>;
+
+  /// Load many plugins at once, returning the result of each one in the same order of `new_plugins`.
+  /// The default implementation loads them one by one in the given order, while a manager that knows the dependencies
+  /// between plugins may override it to load them in the right order.
+  fn load_all<Loader: PluginLoader<PluginId, PluginType>>( &mut self, new_plugins: impl IntoIterator<Item = Loader> ) -> Vec<Result<(), ManagerLoadError>> {
+    new_plugins.into_iter().map( |new_plugin| self.load( new_plugin ) ).collect()
+  }
 }
 
 /// Get the plugins loaded in the PluginManager.
@@ -93,7 +126,7 @@ pub trait PluginManagerLoad<PluginId, PluginType, ManagerLoadError>
 /// let plugin_manager = MyPluginManager::new();
 /// assert_eq!( plugin_manager.get( &String::from("PluginName") ), Some(&String::from("PlugintType")))
 /// ```
-pub trait PluginManagerGet<PluginId, PluginType> {
+pub trait PluginManagerGet<PluginId, PluginType: ?Sized> {
   fn get( &self, plugin: &PluginId) -> Option<&PluginType>;
 }
 
@@ -0,0 +1,69 @@
+//! Policies deciding if a plugin compiled against a version of aanyx can be loaded by a host using another version.
+//!
+//! The [`DylibPluginManager`](crate::host::DylibPluginManager) consults its policy with the `nyx_version` found in the
+//! plugin declaration and [`CORE_VERSION`](crate::CORE_VERSION). By default it uses [`SemverCompatible`], so a patch release
+//! of aanyx doesn't force a rebuild of every plugin.
+//! ```
+//! use aanyx::host::{CompatibilityPolicy, ExactVersion, SemverCompatible};
+//!
+//! assert!( SemverCompatible.is_compatible( "0.2.1", "0.2.7" ) );
+//! assert!( !SemverCompatible.is_compatible( "0.2.1", "0.3.0" ) );
+//! assert!( !ExactVersion.is_compatible( "0.2.1", "0.2.7" ) );
+//!
+//! // Any closure can be used as a policy
+//! let same_major = |plugin: &str, host: &str| plugin.split('.').next() == host.split('.').next();
+//! assert!( same_major.is_compatible( "1.0.0", "1.4.2" ) );
+//! ```
+
+use semver::Version;
+
+/// Decide if a plugin declaring the aanyx version `plugin` can be used by a host running the aanyx version `host`.
+pub trait CompatibilityPolicy {
+  fn is_compatible( &self, plugin: &str, host: &str ) -> bool;
+}
+
+impl<F: Fn( &str, &str ) -> bool> CompatibilityPolicy for F {
+  fn is_compatible( &self, plugin: &str, host: &str ) -> bool {
+    (self)( plugin, host )
+  }
+}
+
+/// The versions must be equal
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactVersion;
+
+impl CompatibilityPolicy for ExactVersion {
+  fn is_compatible( &self, plugin: &str, host: &str ) -> bool {
+    plugin == host
+  }
+}
+
+/// The versions must follow the semver compatibility rules used by cargo: they are compatible
+/// if their left-most non-zero component is the same, so `0.2.x` is compatible with `0.2.y` and `1.x.y` with `1.z.w`.
+/// Pre-releases are compatible only with the very same version.
+/// ```
+/// use aanyx::host::{CompatibilityPolicy, SemverCompatible};
+///
+/// assert!( SemverCompatible.is_compatible( "1.2.0", "1.0.3" ) );
+/// assert!( !SemverCompatible.is_compatible( "0.0.1", "0.0.2" ) );
+/// assert!( !SemverCompatible.is_compatible( "1.0.0-alpha", "1.0.0" ) );
+/// assert!( !SemverCompatible.is_compatible( "not a version", "1.0.0" ) );
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SemverCompatible;
+
+impl CompatibilityPolicy for SemverCompatible {
+  fn is_compatible( &self, plugin: &str, host: &str ) -> bool {
+    let (Ok( plugin ), Ok( host )) = ( Version::parse( plugin ), Version::parse( host ) ) else {
+      return false;
+    };
+    if !plugin.pre.is_empty() || !host.pre.is_empty() {
+      return plugin == host;
+    }
+    match ( plugin.major, host.major ) {
+      ( 0, 0 ) if plugin.minor == 0 && host.minor == 0 => plugin.patch == host.patch,
+      ( 0, 0 ) => plugin.minor == host.minor,
+      ( plugin_major, host_major ) => plugin_major == host_major,
+    }
+  }
+}
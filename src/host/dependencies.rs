@@ -0,0 +1,37 @@
+//! Ordering of plugins that depend on each other.
+
+/// Sort the nodes so that every node comes after the nodes with the ids it depends on.
+/// Dependencies that are not the id of any node are ignored.
+///
+/// Returns the sorted indexes of the nodes and the indexes of the nodes that cannot be sorted,
+/// because they are part of a cycle or depend on a node of a cycle.
+pub(crate) fn load_order( ids: &[&str], dependencies: &[Vec<&str>] ) -> (Vec<usize>, Vec<usize>) {
+  // For every node, the nodes waiting for it and the number of nodes it is still waiting for
+  let mut dependents = vec![Vec::new(); ids.len()];
+  let mut waiting = vec![0usize; ids.len()];
+  for (node, node_dependencies) in dependencies.iter().enumerate() {
+    for dependency in node_dependencies {
+      for (other, id) in ids.iter().enumerate() {
+        if id == dependency && other != node {
+          dependents[other].push( node );
+          waiting[node] += 1;
+        }
+      }
+    }
+  }
+
+  let mut order: Vec<usize> = ( 0..ids.len() ).filter( |&node| waiting[node] == 0 ).collect();
+  let mut next = 0;
+  while next < order.len() {
+    for &dependent in &dependents[order[next]] {
+      waiting[dependent] -= 1;
+      if waiting[dependent] == 0 {
+        order.push( dependent );
+      }
+    }
+    next += 1;
+  }
+
+  let blocked = ( 0..ids.len() ).filter( |&node| waiting[node] > 0 ).collect();
+  ( order, blocked )
+}
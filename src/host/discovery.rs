@@ -0,0 +1,104 @@
+//! Find and load all the plugins contained in a directory.
+//! ```no_run
+//! use aanyx::import_plugin;
+//! use aanyx::host::{discover, Discovered, DylibPluginManager};
+//! # trait MyPluginTrait {}
+//!
+//! let mut manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+//! for (path, outcome) in unsafe { discover( &mut manager, "plugins", import_plugin!( dyn MyPluginTrait ) ) }.unwrap() {
+//!   match outcome {
+//!     Discovered::Loaded => println!( "Loaded {}", path.display() ),
+//!     Discovered::Skipped => println!( "{} doesn't contain a plugin", path.display() ),
+//!     Discovered::Failed( error ) => println!( "Cannot load {}: {error}", path.display() ),
+//!   }
+//! }
+//! ```
+
+use std::env::consts::DLL_EXTENSION;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::host::{Dylib, DylibLoader, LoadError, PluginManagerLoad};
+
+/// What happened to a library found by [`discover`]
+#[derive(Debug)]
+pub enum Discovered<ManagerLoadError> {
+  /// The library exports the declaration and it has been loaded by the manager
+  Loaded,
+  /// The library doesn't export the requested declaration, so it has been ignored
+  Skipped,
+  /// The library exports the declaration but it could not be loaded
+  Failed( DiscoveryError<ManagerLoadError> ),
+}
+
+/// The reasons why a library found by [`discover`] has not been loaded
+#[derive(Debug)]
+pub enum DiscoveryError<ManagerLoadError> {
+  /// The file could not be opened as a shared library
+  Open( LoadError ),
+  /// The manager refused to load the library
+  Load( ManagerLoadError ),
+}
+
+impl<ManagerLoadError: fmt::Display> fmt::Display for DiscoveryError<ManagerLoadError> {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    match self {
+      Self::Open( error ) => error.fmt( f ),
+      Self::Load( error ) => error.fmt( f ),
+    }
+  }
+}
+
+impl<ManagerLoadError: Error + 'static> Error for DiscoveryError<ManagerLoadError> {
+  fn source( &self ) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::Open( error ) => Some( error ),
+      Self::Load( error ) => Some( error ),
+    }
+  }
+}
+
+/// Open every shared library in `dir` (not recursively) and load through the `manager` the ones exporting `declaration`,
+/// which should be generated with [`import_plugin!`](crate::import_plugin).
+///
+/// Only the files with the extension used by the platform for shared libraries are considered (`so`, `dylib` or `dll`).
+/// The libraries exporting the declaration are passed all together to [`PluginManagerLoad::load_all`].
+/// The result contains an entry for each of them, sorted by path. It fails only if the directory cannot be read.
+///
+/// ## Safety
+/// Every shared library in `dir` is opened, see [`DylibLoader::open`]: the directory must contain only trusted libraries.
+pub unsafe fn discover<Manager, ManagerLoadError>( manager: &mut Manager, dir: impl AsRef<Path>, declaration: &[u8] ) -> io::Result<Vec<(PathBuf, Discovered<ManagerLoadError>)>>
+where Manager: PluginManagerLoad<PathBuf, Dylib, ManagerLoadError> {
+  let mut paths = Vec::new();
+  for entry in dir.as_ref().read_dir()? {
+    let path = entry?.path();
+    if path.is_file() && path.extension().is_some_and( |extension| extension == DLL_EXTENSION ) {
+      paths.push( path );
+    }
+  }
+  paths.sort();
+
+  let mut outcomes = Vec::new();
+  let mut loaders = Vec::new();
+  for path in paths {
+    match unsafe { DylibLoader::open( &path ) } {
+      Ok( loader ) if !loader.exports( declaration ) => outcomes.push( (path, Discovered::Skipped) ),
+      Ok( loader ) => {
+        loaders.push( loader );
+        outcomes.push( (path, Discovered::Loaded) );
+      }
+      Err( error ) => outcomes.push( (path, Discovered::Failed( DiscoveryError::Open( error ) )) ),
+    }
+  }
+
+  // The libraries are loaded together, so the manager can load them following their dependencies
+  let mut results = manager.load_all( loaders ).into_iter();
+  for (_, outcome) in outcomes.iter_mut().filter( |(_, outcome)| matches!( outcome, Discovered::Loaded ) ) {
+    if let Some( Err( error ) ) = results.next() {
+      *outcome = Discovered::Failed( DiscoveryError::Load( error ) );
+    }
+  }
+  Ok( outcomes )
+}
@@ -0,0 +1,880 @@
+//! A plugin manager for plugins compiled as shared libraries (`cdylib`) and exported with [`export_plugin!`](crate::export_plugin).
+//!
+//! The [`DylibLoader`] opens a library, while the [`DylibPluginManager`] looks for the plugin declaration,
+//! calls its `register` function and keeps every registered plugin next to the library that contains its code.
+//! ```no_run
+//! use aanyx::import_plugin;
+//! use aanyx::host::{DylibLoader, DylibPluginManager, PluginManagerGet, PluginManagerLoad};
+//! # trait MyPluginTrait { fn run( &self ); }
+//!
+//! let mut manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+//! manager.load( unsafe { DylibLoader::open( "plugins/libmy_plugin.so" ) }.unwrap() ).unwrap();
+//!
+//! if let Some( plugin ) = manager.get( &String::from("MyPlugin") ) {
+//!   plugin.run();
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::ffi::{c_void, OsString};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use libloading::Library;
+use semver::{Version, VersionReq};
+
+use crate::abi::{RStr, StableInterface, StableRegistrar};
+use crate::host::dependencies::load_order;
+use crate::host::{CompatibilityPolicy, PluginLoader, PluginManagerGet, PluginManagerLoad, PluginManagerReload, PluginManagerUnload, SemverCompatible};
+use crate::plugin::{HostContext, PluginDeclaration, PluginMetadata, PluginRegistrar, PluginState, RegisterError, DECLARATION_VERSION};
+
+/// The reasons why a library could not be loaded by the [`DylibPluginManager`]
+#[derive(Debug)]
+pub enum LoadError {
+  /// The shared library could not be opened
+  LibraryOpenFailed( libloading::Error ),
+  /// The shared library could not be copied before opening it, see [`DylibLoader::open_shadowed`]
+  ShadowCopyFailed( io::Error ),
+  /// The library doesn't export the plugin declaration requested by the manager
+  SymbolNotFound { symbol: String, source: libloading::Error },
+  /// The plugin has been compiled with a different version of rustc
+  RustcMismatch { plugin: String, host: String },
+  /// The plugin has been compiled against a version of aanyx rejected by the [`CompatibilityPolicy`]
+  CoreVersionMismatch { plugin: String, host: String },
+  /// The plugin declaration has a layout different from the one known by the host, so none of its other fields can be read
+  DeclarationMismatch { plugin: u32, host: u32 },
+  /// The fingerprint of the plugin interface is not the one expected by the host, see [`DylibPluginManager::with_fingerprint`]
+  FingerprintMismatch { plugin: u64, host: u64 },
+  /// No loaded plugin has the id and a version matching the requirement of a dependency
+  DependencyNotSatisfied { plugin: String, dependency: String, requirement: String },
+  /// The plugin is part of a dependency cycle, or depends on a plugin that is. Contains the ids of all the plugins involved
+  DependencyCycle( Vec<String> ),
+  /// The reloaded plugin rejected the state exported by its previous build. The plugin has been unloaded, like its previous build
+  StateRejected { plugin: String, message: String },
+  /// The plugin registered names that are already taken and the [`CollisionPolicy`] is `Reject`. Contains the taken names
+  NameCollision { plugin: String, names: Vec<String> },
+  /// The `register` function of the plugin panicked. The panic has been caught and none of its plugins has been loaded
+  PluginPanicked { plugin: String, message: String },
+}
+
+/// The reasons why a library could not be unloaded by the [`DylibPluginManager`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum UnloadError {
+  /// No library has been loaded from the path
+  NotLoaded,
+  /// Other libraries, with the given paths, depend on the library
+  Required { dependents: Vec<PathBuf> },
+}
+
+impl fmt::Display for UnloadError {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    match self {
+      Self::NotLoaded => write!( f, "the library is not loaded" ),
+      Self::Required { dependents } => write!( f, "the library is required by {} other libraries", dependents.len() ),
+    }
+  }
+}
+
+impl Error for UnloadError {}
+
+impl fmt::Display for LoadError {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    match self {
+      Self::LibraryOpenFailed( error ) => write!( f, "cannot open the library: {error}" ),
+      Self::ShadowCopyFailed( error ) => write!( f, "cannot copy the library: {error}" ),
+      Self::SymbolNotFound { symbol, .. } => write!( f, "the library doesn't export the symbol `{symbol}`" ),
+      Self::RustcMismatch { plugin, host } => write!( f, "the plugin has been compiled with rustc {plugin}, but the host uses rustc {host}" ),
+      Self::CoreVersionMismatch { plugin, host } => write!( f, "the plugin has been compiled against aanyx {plugin}, but the host uses aanyx {host}" ),
+      Self::DeclarationMismatch { plugin, host } => write!( f, "the plugin declaration has layout {plugin}, but the host expects layout {host}" ),
+      Self::FingerprintMismatch { plugin, host } => write!( f, "the plugin interface has fingerprint {plugin:#018x}, but the host expects {host:#018x}" ),
+      Self::DependencyNotSatisfied { plugin, dependency, requirement } => write!( f, "the plugin {plugin} requires {dependency} {requirement}, which is not loaded" ),
+      Self::DependencyCycle( plugins ) => write!( f, "the plugins {} depend on each other", plugins.join( ", " ) ),
+      Self::NameCollision { plugin, names } => write!( f, "the plugin {plugin} registered names already taken: {}", names.join( ", " ) ),
+      Self::StateRejected { plugin, message } => write!( f, "the plugin {plugin} rejected the state of its previous build: {message}" ),
+      Self::PluginPanicked { plugin, message } => write!( f, "the plugin {plugin} panicked while registering: {message}" ),
+    }
+  }
+}
+
+impl Error for LoadError {
+  fn source( &self ) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::LibraryOpenFailed( error ) | Self::SymbolNotFound { source: error, .. } => Some( error ),
+      Self::ShadowCopyFailed( error ) => Some( error ),
+      _ => None,
+    }
+  }
+}
+
+/// Check that the plugin has been compiled with the same rustc of the host and a compatible version of aanyx.
+/// This must be done before calling `register`, because calling it with a different ABI is undefined behaviour.
+/// The layout of the declaration is checked before reading any field after the versions, and the rustc version is not
+/// checked if the host and the plugin support the `stable` ABI.
+fn check_declaration<PluginType: ?Sized>( declaration: &PluginDeclaration<PluginType>, policy: &dyn CompatibilityPolicy, stable_host: bool ) -> Result<(), LoadError> {
+  if !policy.is_compatible( declaration.nyx_version.as_str(), crate::CORE_VERSION ) {
+    return Err( LoadError::CoreVersionMismatch { plugin: declaration.nyx_version.to_string(), host: crate::CORE_VERSION.to_string() } );
+  }
+  if declaration.declaration_version != DECLARATION_VERSION {
+    return Err( LoadError::DeclarationMismatch { plugin: declaration.declaration_version, host: DECLARATION_VERSION } );
+  }
+  let stable = stable_host && declaration.stable_register.is_some();
+  if !stable && declaration.rustc_version.as_str() != crate::RUSTC_VERSION {
+    return Err( LoadError::RustcMismatch { plugin: declaration.rustc_version.to_string(), host: crate::RUSTC_VERSION.to_string() } );
+  }
+  Ok(())
+}
+
+// A copy of a library in the temporary directory, deleted when dropped
+struct ShadowCopy {
+  path: PathBuf,
+}
+
+impl ShadowCopy {
+  fn new( original: &Path ) -> io::Result<Self> {
+    static COUNTER: AtomicUsize = AtomicUsize::new( 0 );
+    let dir = std::env::temp_dir().join( "aanyx-shadow" );
+    fs::create_dir_all( &dir )?;
+    let file_name = original.file_name().ok_or_else( || io::Error::new( io::ErrorKind::InvalidInput, "the path has no file name" ) )?;
+    // The file name is kept as suffix, because some platforms require the library to keep its extension
+    let mut name = OsString::from( format!( "{}-{}-", std::process::id(), COUNTER.fetch_add( 1, Ordering::Relaxed ) ) );
+    name.push( file_name );
+    let path = dir.join( name );
+    fs::copy( original, &path )?;
+    Ok( Self { path } )
+  }
+}
+
+impl Drop for ShadowCopy {
+  fn drop( &mut self ) {
+    let _ = fs::remove_file( &self.path );
+  }
+}
+
+/// A shared library opened by a [`DylibLoader`].
+/// If the library has been opened from a shadow copy, the copy is deleted when the library is unloaded.
+pub struct Dylib {
+  // The fields order matters: the library must be closed before its shadow copy is deleted
+  library: Library,
+  shadow: Option<ShadowCopy>,
+}
+
+impl Dylib {
+  /// True if the library has been opened from a shadow copy
+  pub fn is_shadowed( &self ) -> bool {
+    self.shadow.is_some()
+  }
+
+  /// The path of the shadow copy, if any
+  pub fn shadow_path( &self ) -> Option<&Path> {
+    self.shadow.as_ref().map( |shadow| shadow.path.as_path() )
+  }
+}
+
+/// An opened shared library, ready to be passed to [`DylibPluginManager::load`](PluginManagerLoad::load).
+pub struct DylibLoader {
+  path: PathBuf,
+  dylib: Dylib,
+}
+
+impl DylibLoader {
+  /// Open the shared library at `path`.
+  ///
+  /// ## Safety
+  /// Opening a library runs its initialization routines, which can do anything. The caller must make sure the library
+  /// comes from a trusted source and that its initialization routines are sound.
+  pub unsafe fn open( path: impl AsRef<Path> ) -> Result<Self, LoadError> {
+    let path = path.as_ref().to_path_buf();
+    let library = unsafe { Library::new( &path ) }.map_err( LoadError::LibraryOpenFailed )?;
+    Ok( Self { path, dylib: Dylib { library, shadow: None } } )
+  }
+
+  /// Copy the shared library at `path` to a unique file in the temporary directory and open the copy.
+  /// The copy is deleted when the library is unloaded.
+  ///
+  /// The original file can then be overwritten while the library is loaded, for example by the compiler, without
+  /// crashing the host. Also, the new version is always loaded on reload: opening the same path twice returns
+  /// the library that is already loaded on most platforms.
+  /// The plugins are still identified by the original `path`.
+  ///
+  /// ## Safety
+  /// The same of [`DylibLoader::open`]
+  pub unsafe fn open_shadowed( path: impl AsRef<Path> ) -> Result<Self, LoadError> {
+    let path = path.as_ref().to_path_buf();
+    let shadow = ShadowCopy::new( &path ).map_err( LoadError::ShadowCopyFailed )?;
+    let library = unsafe { Library::new( &shadow.path ) }.map_err( LoadError::LibraryOpenFailed )?;
+    Ok( Self { path, dylib: Dylib { library, shadow: Some( shadow ) } } )
+  }
+
+  /// The path the library was opened from
+  pub fn path( &self ) -> &Path {
+    &self.path
+  }
+
+  /// Check if the library exports the `symbol`, for example a declaration generated with [`import_plugin!`](crate::import_plugin)
+  pub fn exports( &self, symbol: &[u8] ) -> bool {
+    unsafe { self.dylib.library.get::<*const ()>( symbol ).is_ok() }
+  }
+}
+
+impl PluginLoader<PathBuf, Dylib> for DylibLoader {
+  fn into_plugin( self ) -> (PathBuf, Dylib) {
+    ( self.path, self.dylib )
+  }
+}
+
+// A registered plugin. The fields order matters: the plugin must be dropped before its library is unloaded.
+struct DylibPlugin<PluginType: ?Sized> {
+  plugin: Box<PluginType>,
+  path: PathBuf,
+  _library: Arc<Dylib>,
+}
+
+// A loaded library, with the information read from its declaration
+#[allow(improper_ctypes_definitions)]
+struct LoadedLibrary {
+  dylib: Arc<Dylib>,
+  id: String,
+  version: String,
+  dependencies: Vec<(String, String)>,
+  shutdown: Option<unsafe extern "C" fn()>,
+  before_reload: Option<unsafe extern "C" fn()>,
+  state_version: u32,
+  export_state: Option<unsafe extern "C" fn() -> Vec<u8>>,
+  metadata: PluginMetadata,
+  collisions: Vec<Collision>,
+}
+
+// The functions used by the plugins to hand a stable object to the manager and to report a panic, see `StableRegistrar`
+type RegisterStable = unsafe extern "C" fn( *mut c_void, RStr<'_>, *mut c_void ) -> bool;
+type PanickedStable = unsafe extern "C" fn( *mut c_void, RStr<'_> );
+
+// `registrations` points to the `Registrations` of the library being loaded and `plugin` to a `PluginType::Ffi`, which is moved
+unsafe extern "C" fn register_stable<PluginType: StableInterface + ?Sized>( registrations: *mut c_void, name: RStr<'_>, plugin: *mut c_void ) -> bool {
+  let registrations = unsafe { &mut *( registrations as *mut Registrations<'_, PluginType> ) };
+  let plugin = unsafe { std::ptr::read( plugin as *mut PluginType::Ffi ) };
+  registrations.register_plugin( name.as_str(), PluginType::from_ffi( plugin ) ).is_ok()
+}
+
+// `registrations` points to the `Registrations` of the library being loaded
+unsafe extern "C" fn panicked_stable<PluginType: ?Sized>( registrations: *mut c_void, message: RStr<'_> ) {
+  let registrations = unsafe { &mut *( registrations as *mut Registrations<'_, PluginType> ) };
+  registrations.panic = Some( message.as_str().to_string() );
+}
+
+/// What the [`DylibPluginManager`] does when a library registers a plugin with a name that is already taken,
+/// either by another plugin of the same library or by a plugin loaded from another library
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+  /// `register_plugin` fails and the library is not loaded, see [`LoadError::NameCollision`]
+  #[default]
+  Reject,
+  /// The new plugin replaces the existing one
+  Replace,
+  /// The new plugin is registered with the first free name among `name-2`, `name-3`...
+  Suffix,
+}
+
+/// A plugin registered with a name that was already taken, kept by the [`CollisionPolicy`] `Replace` or `Suffix`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+  /// The name passed to `register_plugin`
+  pub name: String,
+  /// The name the plugin is registered with, which is `name` itself with `Replace`
+  pub registered_as: String,
+}
+
+// Collects the plugins registered by a library during the call to `register`.
+// Also used by the `StaticPluginManager`.
+pub(crate) struct Registrations<'m, PluginType: ?Sized> {
+  pub(crate) plugins: Vec<(String, Box<PluginType>)>,
+  // The names of the plugins loaded from other libraries
+  loaded: HashSet<&'m str>,
+  policy: CollisionPolicy,
+  pub(crate) collisions: Vec<String>,
+  // The plugins registered despite a collision, in the order of registration
+  resolved: Vec<Collision>,
+  // The message of the panic caught while calling `register`
+  pub(crate) panic: Option<String>,
+  context: &'m HostContext,
+}
+
+impl<'m, PluginType: ?Sized> Registrations<'m, PluginType> {
+  pub(crate) fn new( loaded: HashSet<&'m str>, policy: CollisionPolicy, context: &'m HostContext ) -> Self {
+    Self { plugins: Vec::new(), loaded, policy, collisions: Vec::new(), resolved: Vec::new(), panic: None, context }
+  }
+
+  // Call the `register` function of the declaration, catching its panics if it has been exported by `export_plugin!`
+  pub(crate) fn register( &mut self, declaration: &PluginDeclaration<PluginType> ) {
+    match declaration.try_register {
+      Some( try_register ) => self.panic = unsafe { try_register( self ) }.err(),
+      None => unsafe { (declaration.register)( self ) },
+    }
+  }
+
+  fn is_taken( &self, name: &str ) -> bool {
+    self.loaded.contains( name ) || self.plugins.iter().any( |(registered, _)| registered == name )
+  }
+}
+
+impl<PluginType: ?Sized> PluginRegistrar<PluginType> for Registrations<'_, PluginType> {
+  fn register_plugin( &mut self, name: &str, plugin: Box<PluginType> ) -> Result<(), RegisterError> {
+    if !self.is_taken( name ) {
+      self.plugins.push( (name.to_string(), plugin) );
+      return Ok(());
+    }
+    if !self.collisions.iter().any( |collision| collision == name ) {
+      self.collisions.push( name.to_string() );
+    }
+    match self.policy {
+      CollisionPolicy::Reject => return Err( RegisterError::NameTaken( name.to_string() ) ),
+      CollisionPolicy::Replace => {
+        // A plugin loaded from another library is replaced when the registrations are inserted in the manager
+        self.plugins.retain( |(registered, _)| registered != name );
+        self.plugins.push( (name.to_string(), plugin) );
+        self.resolved.push( Collision { name: name.to_string(), registered_as: name.to_string() } );
+      }
+      CollisionPolicy::Suffix => {
+        let registered_as = (2..).map( |suffix| format!( "{name}-{suffix}" ) ).find( |name| !self.is_taken( name ) ).expect( "there is always a free name" );
+        self.plugins.push( (registered_as.clone(), plugin) );
+        self.resolved.push( Collision { name: name.to_string(), registered_as } );
+      }
+    }
+    Ok(())
+  }
+
+  fn context( &self ) -> &HostContext {
+    self.context
+  }
+}
+
+/// Loads plugins from shared libraries exporting a [`PluginDeclaration`] for `PluginType`.
+///
+/// Libraries are identified by their path, so [`PluginManagerLoad`], [`PluginManagerUnload`] and [`PluginManagerReload`]
+/// work on whole libraries, while [`PluginManagerGet`] returns the single plugins by the name used in `register_plugin`.
+/// A library stays loaded as long as at least one of its plugins is alive.
+///
+/// ## Dependencies
+/// A library is loaded only if all the [dependencies](crate::plugin::PluginDependency) listed in its declaration have already been
+/// loaded, and [`PluginManagerLoad::load_all`] sorts the libraries so that dependencies come first.
+/// A library cannot be unloaded while other libraries depend on it, unless [`DylibPluginManager::unload_cascade`] is used.
+///
+/// ## Host context
+/// While they register, the plugins can use the services of the [`HostContext`] given with [`DylibPluginManager::with_context`].
+/// Plugins registered through the stable ABI receive an empty context.
+///
+/// ## Name collisions
+/// Plugin names are unique inside a manager. When a library registers a name that is already taken, the manager follows its
+/// [`CollisionPolicy`], which by default rejects the library. The collisions resolved by the other policies are returned by
+/// [`DylibPluginManager::load_with_collisions`] and listed by [`DylibPluginManager::collisions`].
+///
+/// ## Lifecycle
+/// The hooks declared with [`export_plugin!`](crate::export_plugin) are called by the manager: `init` after the library
+/// has been loaded, `shutdown` before it is unloaded (also when the manager is dropped) and `before_reload` before it is reloaded.
+/// When a library is reloaded, the state returned by its `export_state` hook is passed to the `import_state` hook of the new
+/// build, together with the `state_version` of the old build.
+///
+/// ## Safety
+/// All the unsafe calls are performed inside the manager. Before calling `register` the manager checks that the library
+/// has been compiled with the same version of rustc of the host and a version of aanyx accepted by its
+/// [`CompatibilityPolicy`], see [`LoadError`].
+/// Plugins exported with a [`stable_abi`](crate::stable_abi) interface can be compiled with another version of rustc,
+/// if the manager is created with [`DylibPluginManager::with_stable_abi`].
+/// It is still responsibility of the host to request the same `PluginType` used by the plugin: the name of the declaration only
+/// matches the name of the type, so [`DylibPluginManager::with_fingerprint`] should be used to also check its methods.
+pub struct DylibPluginManager<PluginType: ?Sized> {
+  declaration: &'static [u8],
+  policy: Box<dyn CompatibilityPolicy>,
+  collision: CollisionPolicy,
+  fingerprint: Option<u64>,
+  stable: Option<(RegisterStable, PanickedStable)>,
+  context: HostContext,
+  plugins: HashMap<String, DylibPlugin<PluginType>>,
+  libraries: HashMap<PathBuf, LoadedLibrary>,
+}
+
+impl<PluginType: ?Sized> DylibPluginManager<PluginType> {
+  /// Create a manager that loads the declaration named `declaration`, which should be generated using [`import_plugin!`](crate::import_plugin)
+  /// The manager accepts plugins compiled against versions of aanyx that are [`SemverCompatible`] with the host.
+  pub fn new( declaration: &'static [u8] ) -> Self {
+    Self { declaration, policy: Box::new( SemverCompatible ), collision: CollisionPolicy::default(), fingerprint: None, stable: None, context: HostContext::new(), plugins: HashMap::new(), libraries: HashMap::new() }
+  }
+
+  /// Replace the policy used to check the aanyx version of the plugins
+  /// ```
+  /// use aanyx::import_plugin;
+  /// use aanyx::host::{DylibPluginManager, ExactVersion};
+  /// # trait MyPluginTrait {}
+  ///
+  /// let manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) ).with_policy( ExactVersion );
+  /// ```
+  pub fn with_policy( mut self, policy: impl CompatibilityPolicy + 'static ) -> Self {
+    self.policy = Box::new( policy );
+    self
+  }
+
+  /// Replace the policy followed when a library registers a plugin with a name that is already taken
+  /// ```
+  /// use aanyx::import_plugin;
+  /// use aanyx::host::{CollisionPolicy, DylibPluginManager};
+  /// # trait MyPluginTrait {}
+  ///
+  /// let manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) ).with_collision_policy( CollisionPolicy::Suffix );
+  /// ```
+  pub fn with_collision_policy( mut self, collision: CollisionPolicy ) -> Self {
+    self.collision = collision;
+    self
+  }
+
+  /// Load only the plugins whose declaration has the given interface `fingerprint`. Plugins without a fingerprint are refused too.
+  /// ```
+  /// use aanyx::import_plugin;
+  /// use aanyx::host::DylibPluginManager;
+  /// use aanyx::plugin::Interface;
+  ///
+  /// #[aanyx::interface]
+  /// trait MyPluginTrait {
+  ///   fn run( &self );
+  /// }
+  ///
+  /// let manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) )
+  ///   .with_fingerprint( <dyn MyPluginTrait as Interface>::FINGERPRINT );
+  /// ```
+  pub fn with_fingerprint( mut self, fingerprint: u64 ) -> Self {
+    self.fingerprint = Some( fingerprint );
+    self
+  }
+
+  /// Register the plugins declared with `stable_abi = true` through their [`stable_abi`](crate::stable_abi) vtables,
+  /// without checking the version of rustc used to compile them. The other plugins are loaded as usual.
+  pub fn with_stable_abi( mut self ) -> Self where PluginType: StableInterface {
+    self.stable = Some( (register_stable::<PluginType>, panicked_stable::<PluginType>) );
+    self
+  }
+
+  /// Offer the services of the `context` to the plugins while they register, see [`HostContext`]
+  /// ```
+  /// use aanyx::import_plugin;
+  /// use aanyx::host::DylibPluginManager;
+  /// use aanyx::plugin::HostContext;
+  /// # trait MyPluginTrait {}
+  ///
+  /// let manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) )
+  ///   .with_context( HostContext::new().with_service( "greeting", String::from("Hello") ) );
+  /// ```
+  pub fn with_context( mut self, context: HostContext ) -> Self {
+    self.context = context;
+    self
+  }
+
+  /// The services offered to the plugins
+  pub fn context( &self ) -> &HostContext {
+    &self.context
+  }
+
+  /// The services offered to the plugins, which can be changed before loading other libraries
+  pub fn context_mut( &mut self ) -> &mut HostContext {
+    &mut self.context
+  }
+
+  /// The names of all the loaded plugins
+  pub fn plugins( &self ) -> impl Iterator<Item = &str> {
+    self.plugins.keys().map( String::as_str )
+  }
+
+  /// The paths of all the loaded libraries
+  pub fn libraries( &self ) -> impl Iterator<Item = &Path> {
+    self.libraries.keys().map( PathBuf::as_path )
+  }
+
+  /// The library loaded from `path`
+  pub fn library( &self, path: &Path ) -> Option<&Dylib> {
+    self.libraries.get( path ).map( |library| library.dylib.as_ref() )
+  }
+
+  /// The path of the library whose declaration has the given `id`
+  pub fn library_with_id( &self, id: &str ) -> Option<&Path> {
+    self.libraries.iter().find( |(_, library)| library.id == id ).map( |(path, _)| path.as_path() )
+  }
+
+  /// Read the metadata of an opened library without registering its plugins, so that the host can choose which libraries to load.
+  /// The versions of rustc and aanyx are checked as in [`load`](PluginManagerLoad::load), while the fingerprint is not.
+  /// ```no_run
+  /// use aanyx::import_plugin;
+  /// use aanyx::host::{DylibLoader, DylibPluginManager, PluginManagerLoad};
+  /// # trait MyPluginTrait {}
+  ///
+  /// let mut manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+  /// let library = unsafe { DylibLoader::open( "plugins/libmy_plugin.so" ) }.unwrap();
+  /// let metadata = manager.metadata( &library ).unwrap();
+  /// if metadata.capabilities.iter().any( |capability| capability == "spell-check" ) {
+  ///   manager.load( library ).unwrap();
+  /// }
+  /// ```
+  pub fn metadata( &self, library: &DylibLoader ) -> Result<PluginMetadata, LoadError> {
+    self.checked_declaration( &library.dylib ).map( |declaration| PluginMetadata::from( &declaration.metadata ) )
+  }
+
+  /// Load the library as [`PluginManagerLoad::load`], returning the plugins registered with a name already taken, which
+  /// have replaced the existing plugins or have been renamed according to the [`CollisionPolicy`]
+  /// ```no_run
+  /// use aanyx::import_plugin;
+  /// use aanyx::host::{CollisionPolicy, DylibLoader, DylibPluginManager};
+  /// # trait MyPluginTrait {}
+  ///
+  /// let mut manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) ).with_collision_policy( CollisionPolicy::Suffix );
+  /// let collisions = manager.load_with_collisions( unsafe { DylibLoader::open( "plugins/libmy_plugin.so" ) }.unwrap() ).unwrap();
+  /// for collision in collisions {
+  ///   println!( "{} has been registered as {}", collision.name, collision.registered_as );
+  /// }
+  /// ```
+  pub fn load_with_collisions( &mut self, new_plugin: impl PluginLoader<PathBuf, Dylib> ) -> Result<Vec<Collision>, LoadError> {
+    let (path, library) = new_plugin.into_plugin();
+    self.load_library( path, library, None )
+  }
+
+  /// The plugins of the library loaded from `path` that were registered with a name already taken,
+  /// and the names they have been registered with according to the [`CollisionPolicy`]
+  pub fn collisions( &self, path: &Path ) -> Option<&[Collision]> {
+    self.libraries.get( path ).map( |library| library.collisions.as_slice() )
+  }
+
+  /// The metadata of the library loaded from `path`
+  pub fn library_metadata( &self, path: &Path ) -> Option<&PluginMetadata> {
+    self.libraries.get( path ).map( |library| &library.metadata )
+  }
+
+  /// The path of the library containing the plugin `name`
+  pub fn library_of( &self, name: &str ) -> Option<&Path> {
+    self.plugins.get( name ).map( |plugin| plugin.path.as_path() )
+  }
+
+  /// Unload the library at `path` after unloading, recursively, all the libraries that depend on it.
+  /// Returns the paths of the unloaded libraries, in the order they have been unloaded.
+  pub fn unload_cascade( &mut self, path: &Path ) -> Result<Vec<PathBuf>, UnloadError> {
+    if !self.libraries.contains_key( path ) {
+      return Err( UnloadError::NotLoaded );
+    }
+    let mut unloaded = Vec::new();
+    self.unload_with_dependents( path, &mut unloaded, &mut HashSet::new() );
+    Ok( unloaded )
+  }
+
+  // The libraries being unloaded are skipped, so a cycle among the loaded libraries doesn't recurse forever
+  fn unload_with_dependents( &mut self, path: &Path, unloaded: &mut Vec<PathBuf>, visiting: &mut HashSet<PathBuf> ) {
+    if !visiting.insert( path.to_path_buf() ) {
+      return;
+    }
+    for dependent in self.dependents_of( path ) {
+      if self.libraries.contains_key( &dependent ) {
+        self.unload_with_dependents( &dependent, unloaded, visiting );
+      }
+    }
+    self.remove_library( path );
+    unloaded.push( path.to_path_buf() );
+  }
+
+  // The paths of the other libraries that depend on the library at `path`
+  fn dependents_of( &self, path: &Path ) -> Vec<PathBuf> {
+    let Some( library ) = self.libraries.get( path ) else { return Vec::new() };
+    let mut dependents: Vec<PathBuf> = self.libraries.iter()
+      .filter( |(other, dependent)| *other != path && dependent.dependencies.iter().any( |(id, _)| *id == library.id ) )
+      .map( |(other, _)| other.clone() )
+      .collect();
+    dependents.sort();
+    dependents
+  }
+
+  // Call the shutdown hook, then drop the plugins of the library and finally the library itself, unless the caller keeps it
+  fn remove_library( &mut self, path: &Path ) -> Option<Arc<Dylib>> {
+    let library = self.libraries.remove( path )?;
+    if let Some( shutdown ) = library.shutdown {
+      unsafe { shutdown() };
+    }
+    self.plugins.retain( |_, plugin| plugin.path != path );
+    Some( library.dylib )
+  }
+
+  // Call the before_reload hook of the library at `path`, if it is loaded
+  fn before_reload( &self, path: &Path ) {
+    if let Some( before_reload ) = self.libraries.get( path ).and_then( |library| library.before_reload ) {
+      unsafe { before_reload() };
+    }
+  }
+
+  // Call the export_state hook of the library at `path`, tagging the state with the version declared by the library
+  fn export_state( &self, path: &Path ) -> Option<PluginState> {
+    let library = self.libraries.get( path )?;
+    let export_state = library.export_state?;
+    Some( PluginState { version: library.state_version, data: unsafe { export_state() } } )
+  }
+
+  // Whether the plugins of the declaration are registered through the stable ABI
+  fn is_stable( &self, declaration: &PluginDeclaration<PluginType> ) -> bool {
+    self.stable.is_some() && declaration.stable_register.is_some()
+  }
+
+  // Find the declaration in the library and check that its layout is the one known by the host
+  fn checked_declaration<'l>( &self, library: &'l Dylib ) -> Result<&'l PluginDeclaration<PluginType>, LoadError> {
+    let declaration = unsafe { library.library.get::<*const PluginDeclaration<PluginType>>( self.declaration ) }
+      .map_err( |source| LoadError::SymbolNotFound { symbol: String::from_utf8_lossy( self.declaration ).into_owned(), source } )?;
+    let declaration = unsafe { &**declaration };
+    check_declaration( declaration, self.policy.as_ref(), self.stable.is_some() )?;
+    Ok( declaration )
+  }
+
+  // Find the declaration in the library and check that it is safe to call its `register` function
+  fn declaration_of<'l>( &self, library: &'l Dylib ) -> Result<&'l PluginDeclaration<PluginType>, LoadError> {
+    let declaration = self.checked_declaration( library )?;
+    match self.fingerprint {
+      Some( fingerprint ) if fingerprint != declaration.fingerprint => Err( LoadError::FingerprintMismatch { plugin: declaration.fingerprint, host: fingerprint } ),
+      _ => Ok( declaration ),
+    }
+  }
+
+  // Whether a library loaded from a path different from `path` has the `id` and a version matching the `requirement`
+  fn is_satisfied( &self, path: &Path, id: &str, requirement: &str ) -> bool {
+    VersionReq::parse( requirement ).is_ok_and( |requirement| {
+      self.libraries.iter().any( |(other, library)| {
+        other != path && library.id == id && Version::parse( &library.version ).is_ok_and( |version| requirement.matches( &version ) )
+      })
+    })
+  }
+
+  // Unload, with their dependents, the libraries whose dependencies are no longer loaded, because a library they depend on
+  // has been replaced by a build with another id or version, or could not be reloaded
+  fn unload_unsatisfied( &mut self ) {
+    loop {
+      let unsatisfied = self.libraries.iter()
+        .find( |(path, library)| library.dependencies.iter().any( |(id, requirement)| !self.is_satisfied( path, id, requirement ) ) )
+        .map( |(path, _)| path.clone() );
+      let Some( path ) = unsatisfied else { return };
+      self.unload_with_dependents( &path, &mut Vec::new(), &mut HashSet::new() );
+    }
+  }
+
+  // Check that every dependency is satisfied by a library loaded from a path different from `path`
+  fn check_dependencies( &self, path: &Path, declaration: &PluginDeclaration<PluginType> ) -> Result<(), LoadError> {
+    for dependency in declaration.dependencies.as_slice() {
+      if !self.is_satisfied( path, dependency.id.as_str(), dependency.version.as_str() ) {
+        return Err( LoadError::DependencyNotSatisfied {
+          plugin: declaration.id.to_string(),
+          dependency: dependency.id.to_string(),
+          requirement: dependency.version.to_string(),
+        });
+      }
+    }
+    self.check_cycles( path, declaration )
+  }
+
+  // Check that no library reached through the dependencies of the declaration depends on it, because the libraries
+  // in a cycle could not be unloaded one after the other
+  fn check_cycles( &self, path: &Path, declaration: &PluginDeclaration<PluginType> ) -> Result<(), LoadError> {
+    let mut visited = HashSet::new();
+    let mut pending: Vec<Vec<&str>> = declaration.dependencies.as_slice().iter().map( |dependency| vec![dependency.id.as_str()] ).collect();
+    while let Some( chain ) = pending.pop() {
+      let id = chain[chain.len() - 1];
+      if !visited.insert( id ) {
+        continue;
+      }
+      for (_, library) in self.libraries.iter().filter( |(other, library)| *other != path && library.id == id ) {
+        for (dependency, _) in &library.dependencies {
+          if dependency == declaration.id.as_str() {
+            let mut plugins: Vec<String> = chain.iter().map( ToString::to_string ).collect();
+            plugins.push( declaration.id.to_string() );
+            plugins.sort();
+            return Err( LoadError::DependencyCycle( plugins ) );
+          }
+          let mut next = chain.clone();
+          next.push( dependency );
+          pending.push( next );
+        }
+      }
+    }
+    Ok(())
+  }
+
+  // Load the library and then unload the libraries that depended on the library it replaced, if they are not satisfied anymore
+  fn load_library( &mut self, path: PathBuf, library: Dylib, state: Option<PluginState> ) -> Result<Vec<Collision>, LoadError> {
+    let result = self.register_library( path, library, state );
+    self.unload_unsatisfied();
+    result
+  }
+
+  // Register the plugins of the library, hand it the `state` of the previous build, if any, and call its init hook.
+  // A library already loaded from the same path is shut down before registering the new one.
+  fn register_library( &mut self, path: PathBuf, library: Dylib, state: Option<PluginState> ) -> Result<Vec<Collision>, LoadError> {
+    let declaration = self.declaration_of( &library )?;
+    self.check_dependencies( &path, declaration )?;
+    let id = declaration.id.to_string();
+    let version = declaration.version.to_string();
+    let dependencies = declaration.dependencies.as_slice().iter().map( |dependency| (dependency.id.to_string(), dependency.version.to_string()) ).collect();
+    let metadata = PluginMetadata::from( &declaration.metadata );
+    let (init, shutdown, before_reload) = ( declaration.init, declaration.shutdown, declaration.before_reload );
+    // The state hooks use types whose layout depends on the compiler
+    let (state_version, export_state, import_state) = match self.is_stable( declaration ) {
+      true => ( 0, None, None ),
+      false => ( declaration.state_version, declaration.export_state, declaration.import_state ),
+    };
+
+    self.remove_library( &path );
+    let loaded = self.plugins.keys().map( String::as_str ).collect();
+    let mut registrations = Registrations::new( loaded, self.collision, &self.context );
+    match self.stable.zip( declaration.stable_register ) {
+      Some( ((register_stable, panicked_stable), stable_register) ) => {
+        // SAFETY: `register_stable` and `panicked_stable` have been instantiated for `PluginType`, like `registrations`,
+        // which outlives the registrar
+        let mut registrar = unsafe { StableRegistrar::new( &mut registrations as *mut Registrations<'_, PluginType> as *mut c_void, register_stable, panicked_stable ) };
+        unsafe { stable_register( &mut registrar, declaration ) };
+      }
+      None => registrations.register( declaration ),
+    }
+    if let Some( message ) = registrations.panic {
+      drop( registrations.plugins );
+      return Err( LoadError::PluginPanicked { plugin: id, message } );
+    }
+    if self.collision == CollisionPolicy::Reject && !registrations.collisions.is_empty() {
+      let names = registrations.collisions;
+      drop( registrations.plugins );
+      return Err( LoadError::NameCollision { plugin: id, names } );
+    }
+    let collisions = registrations.resolved;
+    let registrations = registrations.plugins;
+
+    let library = Arc::new( library );
+    for (name, plugin) in registrations {
+      self.plugins.insert( name, DylibPlugin { plugin, path: path.clone(), _library: Arc::clone( &library ) } );
+    }
+    self.libraries.insert( path.clone(), LoadedLibrary { dylib: library, id: id.clone(), version, dependencies, shutdown, before_reload, state_version, export_state, metadata, collisions: collisions.clone() } );
+    if let (Some( state ), Some( import_state )) = (state, import_state) {
+      if let Err( message ) = unsafe { import_state( state ) } {
+        // The library has not been initialized, so it is unloaded without calling its shutdown hook
+        self.plugins.retain( |_, plugin| plugin.path != path );
+        self.libraries.remove( &path );
+        return Err( LoadError::StateRejected { plugin: id, message } );
+      }
+    }
+    if let Some( init ) = init {
+      unsafe { init() };
+    }
+    Ok( collisions )
+  }
+}
+
+impl<PluginType: ?Sized> PluginManagerLoad<PathBuf, Dylib, LoadError> for DylibPluginManager<PluginType> {
+  /// Register all the plugins of the library. If a library with the same path is already loaded, it is shut down and replaced
+  /// before registering the new one, and the libraries depending on it are unloaded if the new one doesn't satisfy them.
+  /// The `register` function is not called if the plugin has been compiled with different versions of rustc or aanyx,
+  /// or if its dependencies are not loaded.
+  fn load( &mut self, new_plugin: impl PluginLoader<PathBuf, Dylib> ) -> Result<(), LoadError> {
+    self.load_with_collisions( new_plugin ).map( drop )
+  }
+
+  /// Load the libraries after their dependencies. If some libraries depend on each other, none of them is loaded
+  /// and [`LoadError::DependencyCycle`] is returned for each one.
+  fn load_all<Loader: PluginLoader<PathBuf, Dylib>>( &mut self, new_plugins: impl IntoIterator<Item = Loader> ) -> Vec<Result<(), LoadError>> {
+    let mut results = Vec::new();
+    let mut pending = Vec::new();
+    // The ids are copied, because they live in the libraries, which are unloaded if they fail to load
+    let mut ids = Vec::new();
+    let mut dependencies = Vec::new();
+    for (index, new_plugin) in new_plugins.into_iter().enumerate() {
+      let (path, library) = new_plugin.into_plugin();
+      match self.declaration_of( &library ) {
+        Ok( declaration ) => {
+          ids.push( declaration.id.to_string() );
+          dependencies.push( declaration.dependencies.as_slice().iter().map( |dependency| dependency.id.to_string() ).collect::<Vec<_>>() );
+          pending.push( (index, Some( (path, library) )) );
+          results.push( Ok(()) );
+        }
+        Err( error ) => results.push( Err( error ) ),
+      }
+    }
+
+    let ids: Vec<&str> = ids.iter().map( String::as_str ).collect();
+    let dependencies: Vec<Vec<&str>> = dependencies.iter().map( |dependencies| dependencies.iter().map( String::as_str ).collect() ).collect();
+    let (order, blocked) = load_order( &ids, &dependencies );
+
+    let mut cycle: Vec<String> = blocked.iter().map( |&node| ids[node].to_string() ).collect();
+    cycle.sort();
+    for node in blocked {
+      results[pending[node].0] = Err( LoadError::DependencyCycle( cycle.clone() ) );
+    }
+    for node in order {
+      let (path, library) = pending[node].1.take().expect( "every library is loaded once" );
+      results[pending[node].0] = self.load_library( path, library, None ).map( drop );
+    }
+    results
+  }
+}
+
+impl<PluginType: ?Sized> Drop for DylibPluginManager<PluginType> {
+  /// Unload all the libraries, calling their shutdown hooks. The libraries are unloaded before their dependencies.
+  fn drop( &mut self ) {
+    while let Some( path ) = self.libraries.keys().next().cloned() {
+      self.unload_with_dependents( &path, &mut Vec::new(), &mut HashSet::new() );
+    }
+  }
+}
+
+impl<PluginType: ?Sized> PluginManagerGet<String, PluginType> for DylibPluginManager<PluginType> {
+  fn get( &self, plugin: &String ) -> Option<&PluginType> {
+    self.plugins.get( plugin ).map( |plugin| plugin.plugin.as_ref() )
+  }
+}
+
+impl<PluginType: ?Sized> PluginManagerUnload<PathBuf, Dylib, UnloadError> for DylibPluginManager<PluginType> {
+  /// Drop all the plugins registered by the library and then unload the library.
+  /// Fails if the library is not loaded or if other libraries depend on it.
+  fn unload( &mut self, old_plugin: &PathBuf ) -> Result<(), UnloadError> {
+    if !self.libraries.contains_key( old_plugin ) {
+      return Err( UnloadError::NotLoaded );
+    }
+    let dependents = self.dependents_of( old_plugin );
+    if !dependents.is_empty() {
+      return Err( UnloadError::Required { dependents } );
+    }
+    self.remove_library( old_plugin );
+    Ok(())
+  }
+}
+
+impl<PluginType: ?Sized> PluginManagerReload<PathBuf, Dylib, LoadError> for DylibPluginManager<PluginType> {
+  /// Unload the library and open it again from the same path. If the library was opened from a shadow copy,
+  /// a new shadow copy is made. If the new library cannot be loaded, the old plugins are not restored.
+  /// The libraries depending on the old library are unloaded, with their dependents, if the new one doesn't satisfy them.
+  /// The file is trusted because it was trusted when opened the first time, use [`DylibPluginManager::reload_as_new`] to
+  /// open the new file explicitly.
+  ///
+  /// The state exported by the old library is handed to the new one. If the new library rejects it,
+  /// [`LoadError::StateRejected`] is returned and the new library is unloaded too.
+  fn reload( &mut self, plugin: &PathBuf ) -> Result<(), LoadError> {
+    self.before_reload( plugin );
+    let state = self.export_state( plugin );
+    let shadowed = self.remove_library( plugin ).is_some_and( |library| library.is_shadowed() );
+    // SAFETY: the library at this path has already been opened, so the caller of `open` vouched for it
+    match unsafe { if shadowed { DylibLoader::open_shadowed( plugin ) } else { DylibLoader::open( plugin ) } } {
+      Ok( loader ) => {
+        let (path, library) = loader.into_plugin();
+        self.load_library( path, library, state ).map( drop )
+      }
+      Err( error ) => {
+        self.unload_unsatisfied();
+        Err( error )
+      }
+    }
+  }
+
+  /// Unload the old library and load the new one, handing it the state exported by the old library as in [`DylibPluginManager::reload`]
+  fn reload_as_new( &mut self, new_plugin: impl PluginLoader<PathBuf, Dylib>, old_plugin: &PathBuf ) -> Result<(), LoadError> {
+    self.before_reload( old_plugin );
+    let state = self.export_state( old_plugin );
+    self.remove_library( old_plugin );
+    let (path, library) = new_plugin.into_plugin();
+    self.load_library( path, library, state ).map( drop )
+  }
+}
@@ -0,0 +1,364 @@
+//! Read what a shared library offers without opening it.
+//!
+//! Opening a library with [`DylibLoader`](crate::host::DylibLoader) runs its initialization routines, so any code in it is
+//! executed. [`inspect`] instead reads the file as data: it lists the plugin declarations in its dynamic symbol table and the
+//! metadata that [`export_plugin!`](crate::export_plugin) copies in a custom section.
+//! Only ELF files, used by Linux and most other Unix systems, are supported.
+//! ```no_run
+//! use aanyx::import_plugin;
+//! use aanyx::host::inspect;
+//!
+//! let inspection = inspect( "plugins/libmy_plugin.so" ).unwrap();
+//! for declaration in &inspection.declarations {
+//!   println!( "{} exports {}", declaration.metadata.as_ref().map_or( "?", |metadata| &metadata.name ), declaration.plugin_type );
+//! }
+//! if inspection.declaration( import_plugin!( dyn MyPluginTrait ) ).is_some() {
+//!   // Open and load the library
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::plugin::{PluginMetadata, METADATA_RECORD_VERSION, METADATA_SECTION};
+
+const DECLARATION_PREFIX: &str = "plugin_declaration_";
+
+// The section types and the special section index used by the inspector
+const SHT_NOBITS: u32 = 8;
+const SHT_DYNSYM: u32 = 11;
+const SHN_UNDEF: u16 = 0;
+const SHN_XINDEX: u16 = 0xffff;
+
+/// The reasons why a file could not be inspected
+#[derive(Debug)]
+pub enum InspectError {
+  /// The file could not be read
+  Io( io::Error ),
+  /// The file is not an ELF file
+  NotElf,
+  /// The file is an ELF file, but its content is inconsistent. Contains what could not be read
+  Malformed( &'static str ),
+}
+
+impl fmt::Display for InspectError {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    match self {
+      Self::Io( error ) => write!( f, "cannot read the file: {error}" ),
+      Self::NotElf => write!( f, "the file is not an ELF file" ),
+      Self::Malformed( what ) => write!( f, "the ELF file is malformed: invalid {what}" ),
+    }
+  }
+}
+
+impl Error for InspectError {
+  fn source( &self ) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::Io( error ) => Some( error ),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for InspectError {
+  fn from( error: io::Error ) -> Self {
+    Self::Io( error )
+  }
+}
+
+/// A plugin declaration exported by an inspected library
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InspectedDeclaration {
+  /// The name of the symbol, the one generated by [`import_plugin!`](crate::import_plugin)
+  pub symbol: String,
+  /// The plugin type, recovered from the symbol, see [`plugin_type_of`]
+  pub plugin_type: String,
+  /// The metadata of the declaration. It's missing if the declaration has not been exported with
+  /// [`export_plugin!`](crate::export_plugin) or the [`plugin`](crate::plugin) attribute
+  pub metadata: Option<PluginMetadata>,
+}
+
+/// What an inspected library offers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inspection {
+  /// The plugin declarations, sorted by symbol
+  pub declarations: Vec<InspectedDeclaration>,
+}
+
+impl Inspection {
+  /// The declaration with the given `symbol`, usually generated with [`import_plugin!`](crate::import_plugin)
+  pub fn declaration( &self, symbol: &[u8] ) -> Option<&InspectedDeclaration> {
+    self.declarations.iter().find( |declaration| declaration.symbol.as_bytes() == symbol )
+  }
+}
+
+/// Read the plugin declarations exported by the library at `path`, without opening it
+pub fn inspect( path: impl AsRef<Path> ) -> Result<Inspection, InspectError> {
+  inspect_bytes( &fs::read( path )? )
+}
+
+/// Read the plugin declarations exported by a library, given the content of its file
+pub fn inspect_bytes( data: &[u8] ) -> Result<Inspection, InspectError> {
+  let elf = Elf::parse( data )?;
+  let mut metadata = Vec::new();
+  if let Some( section ) = elf.section_named( METADATA_SECTION )? {
+    metadata = metadata_records( elf.section_data( section )? )?;
+  }
+
+  let mut declarations = Vec::new();
+  for symbol in elf.dynamic_symbols()? {
+    let Some( plugin_type ) = plugin_type_of( &symbol ) else { continue };
+    let metadata = metadata.iter().find( |(record, _)| *record == symbol ).map( |(_, metadata)| metadata.clone() );
+    declarations.push( InspectedDeclaration { symbol, plugin_type, metadata } );
+  }
+  declarations.sort_by( |a, b| a.symbol.cmp( &b.symbol ) );
+  declarations.dedup_by( |a, b| a.symbol == b.symbol );
+  Ok( Inspection { declarations } )
+}
+
+/// The plugin type exported by a declaration, reversing the mangling of [`import_plugin!`](crate::import_plugin#mangling).
+/// Returns `None` if the symbol is not a plugin declaration.
+/// ```
+/// use aanyx::host::inspect::plugin_type_of;
+///
+/// assert_eq!( plugin_type_of( "plugin_declaration_dyn_Greeter" ).unwrap(), "dyn Greeter" );
+/// assert_eq!( plugin_type_of( "plugin_declaration_dyn_api_3a_3aHandler_3cu8_3e" ).unwrap(), "dyn api::Handler<u8>" );
+/// assert_eq!( plugin_type_of( "plugin_declaration_MyPlugin" ).unwrap(), "MyPlugin" );
+/// assert_eq!( plugin_type_of( "plugin_declaration_dyn_a_5f3a_5f3aB" ).unwrap(), "dyn a_3a_3aB" );
+/// assert_eq!( plugin_type_of( "plugin_declaration__64yn_5fGreeter" ).unwrap(), "dyn_Greeter" );
+/// assert!( plugin_type_of( "main" ).is_none() );
+/// ```
+pub fn plugin_type_of( symbol: &str ) -> Option<String> {
+  let rest = symbol.strip_prefix( DECLARATION_PREFIX ).filter( |rest| !rest.is_empty() )?;
+  let (kind, rest) = match rest.strip_prefix( "dyn_" ) {
+    Some( rest ) => ("dyn ", rest),
+    None => ("", rest),
+  };
+  Some( format!( "{kind}{}", unescape( rest ).unwrap_or_else( || rest.to_string() ) ) )
+}
+
+// Reverse the escaping of the types that are not a single identifier, or `None` if `escaped` is not escaped
+fn unescape( escaped: &str ) -> Option<String> {
+  if !escaped.contains( '_' ) {
+    return None;
+  }
+  let bytes = escaped.as_bytes();
+  let mut out = Vec::new();
+  let mut index = 0;
+  while index < bytes.len() {
+    if bytes[index] == b'_' {
+      let hex = escaped.get( index + 1..index + 3 ).filter( |hex| hex.bytes().all( |c| matches!( c, b'0'..=b'9' | b'a'..=b'f' ) ) )?;
+      out.push( u8::from_str_radix( hex, 16 ).ok()? );
+      index += 3;
+    } else {
+      out.push( bytes[index] );
+      index += 1;
+    }
+  }
+  // The escapes are the bytes of the UTF-8 encoding of the type
+  String::from_utf8( out ).ok()
+}
+
+// Read the records written by `aanyx::plugin::metadata_record`
+fn metadata_records( mut data: &[u8] ) -> Result<Vec<(String, PluginMetadata)>, InspectError> {
+  let mut records = Vec::new();
+  loop {
+    // The linker may align the records of different declarations
+    while let [0, rest @ ..] = data {
+      data = rest;
+    }
+    if data.is_empty() {
+      return Ok( records );
+    }
+    let mut reader = Reader { data };
+    if reader.take( 1 )? != [METADATA_RECORD_VERSION] {
+      return Err( InspectError::Malformed( "metadata version" ) );
+    }
+    let len = reader.u32()? as usize;
+    let record = reader.take( len )?;
+    data = reader.data;
+
+    let mut reader = Reader { data: record };
+    let symbol = reader.string()?;
+    let name = reader.string()?;
+    let version = reader.string()?;
+    let authors = reader.string()?;
+    let description = reader.string()?;
+    let license = reader.string()?;
+    let mut capabilities = Vec::new();
+    for _ in 0..reader.u32()? {
+      capabilities.push( reader.string()? );
+    }
+    let authors = authors.split( ':' ).filter( |author| !author.is_empty() ).map( str::to_string ).collect();
+    records.push( (symbol, PluginMetadata { name, version, authors, description, license, capabilities }) );
+  }
+}
+
+// Reads the little endian numbers and strings of the metadata records
+struct Reader<'d> {
+  data: &'d [u8],
+}
+
+impl<'d> Reader<'d> {
+  fn take( &mut self, len: usize ) -> Result<&'d [u8], InspectError> {
+    if len > self.data.len() {
+      return Err( InspectError::Malformed( "metadata" ) );
+    }
+    let (taken, rest) = self.data.split_at( len );
+    self.data = rest;
+    Ok( taken )
+  }
+
+  fn u32( &mut self ) -> Result<u32, InspectError> {
+    Ok( u32::from_le_bytes( self.take( 4 )?.try_into().expect( "4 bytes have been taken" ) ) )
+  }
+
+  fn string( &mut self ) -> Result<String, InspectError> {
+    let len = self.u32()? as usize;
+    String::from_utf8( self.take( len )?.to_vec() ).map_err( |_| InspectError::Malformed( "metadata" ) )
+  }
+}
+
+// The fields of a section header used by the inspector
+struct Section {
+  name: u32,
+  kind: u32,
+  offset: u64,
+  size: u64,
+  link: u32,
+}
+
+// An ELF file, either 32 or 64 bits and either little or big endian
+struct Elf<'d> {
+  data: &'d [u8],
+  is_64: bool,
+  is_little: bool,
+  sections: Vec<Section>,
+  names: u32,
+}
+
+impl<'d> Elf<'d> {
+  fn parse( data: &'d [u8] ) -> Result<Self, InspectError> {
+    if data.len() < 16 || data[..4] != *b"\x7fELF" {
+      return Err( InspectError::NotElf );
+    }
+    let is_64 = match data[4] {
+      1 => false,
+      2 => true,
+      _ => return Err( InspectError::Malformed( "class" ) ),
+    };
+    let is_little = match data[5] {
+      1 => true,
+      2 => false,
+      _ => return Err( InspectError::Malformed( "data encoding" ) ),
+    };
+    let mut elf = Self { data, is_64, is_little, sections: Vec::new(), names: 0 };
+
+    let (offset, entry_size, count, names) = if is_64 {
+      ( elf.u64( 0x28 )?, elf.u16( 0x3a )?, elf.u16( 0x3c )?, elf.u16( 0x3e )? )
+    } else {
+      ( elf.u32( 0x20 )? as u64, elf.u16( 0x2e )?, elf.u16( 0x30 )?, elf.u16( 0x32 )? )
+    };
+    if offset == 0 {
+      return Ok( elf );
+    }
+    if entry_size != if is_64 { 64 } else { 40 } {
+      return Err( InspectError::Malformed( "section header size" ) );
+    }
+    // With many sections, their count and the index of the names are stored in the first section
+    let first = elf.section( offset )?;
+    let count = if count == 0 { first.size } else { count as u64 };
+    elf.names = if names == SHN_XINDEX { first.link } else { names as u32 };
+    // The count is checked against the size of the file before reserving anything for the sections
+    if count > ( data.len() as u64 ).saturating_sub( offset ) / entry_size as u64 {
+      return Err( InspectError::Malformed( "section headers" ) );
+    }
+    for index in 0..count {
+      let header = index.checked_mul( entry_size as u64 ).and_then( |at| at.checked_add( offset ) ).ok_or( InspectError::Malformed( "section headers" ) )?;
+      let section = elf.section( header )?;
+      elf.sections.push( section );
+    }
+    Ok( elf )
+  }
+
+  fn bytes( &self, at: u64, len: u64 ) -> Result<&'d [u8], InspectError> {
+    let start = usize::try_from( at ).map_err( |_| InspectError::Malformed( "offset" ) )?;
+    let end = usize::try_from( len ).ok().and_then( |len| start.checked_add( len ) ).ok_or( InspectError::Malformed( "offset" ) )?;
+    self.data.get( start..end ).ok_or( InspectError::Malformed( "offset" ) )
+  }
+
+  fn u16( &self, at: u64 ) -> Result<u16, InspectError> {
+    let bytes = self.bytes( at, 2 )?.try_into().expect( "2 bytes have been read" );
+    Ok( if self.is_little { u16::from_le_bytes( bytes ) } else { u16::from_be_bytes( bytes ) } )
+  }
+
+  fn u32( &self, at: u64 ) -> Result<u32, InspectError> {
+    let bytes = self.bytes( at, 4 )?.try_into().expect( "4 bytes have been read" );
+    Ok( if self.is_little { u32::from_le_bytes( bytes ) } else { u32::from_be_bytes( bytes ) } )
+  }
+
+  fn u64( &self, at: u64 ) -> Result<u64, InspectError> {
+    let bytes = self.bytes( at, 8 )?.try_into().expect( "8 bytes have been read" );
+    Ok( if self.is_little { u64::from_le_bytes( bytes ) } else { u64::from_be_bytes( bytes ) } )
+  }
+
+  // An address or a size, whose width depends on the class
+  fn word( &self, at: u64 ) -> Result<u64, InspectError> {
+    if self.is_64 { self.u64( at ) } else { self.u32( at ).map( u64::from ) }
+  }
+
+  // The section header at `at`
+  fn section( &self, at: u64 ) -> Result<Section, InspectError> {
+    let (offset, size, link) = if self.is_64 { (24, 32, 40) } else { (16, 20, 24) };
+    Ok( Section {
+      name: self.u32( at )?,
+      kind: self.u32( at + 4 )?,
+      offset: self.word( at + offset )?,
+      size: self.word( at + size )?,
+      link: self.u32( at + link )?,
+    })
+  }
+
+  fn section_data( &self, section: &Section ) -> Result<&'d [u8], InspectError> {
+    if section.kind == SHT_NOBITS {
+      return Ok( &[] );
+    }
+    self.bytes( section.offset, section.size )
+  }
+
+  // The null terminated string at `offset` of the string table in the section with index `table`
+  fn string( &self, table: u32, offset: u32 ) -> Result<&'d str, InspectError> {
+    let table = self.sections.get( table as usize ).ok_or( InspectError::Malformed( "string table" ) )?;
+    let data = self.section_data( table )?.get( offset as usize.. ).ok_or( InspectError::Malformed( "string table" ) )?;
+    let end = data.iter().position( |&c| c == 0 ).ok_or( InspectError::Malformed( "string table" ) )?;
+    std::str::from_utf8( &data[..end] ).map_err( |_| InspectError::Malformed( "string table" ) )
+  }
+
+  fn section_named( &self, name: &str ) -> Result<Option<&Section>, InspectError> {
+    for section in &self.sections {
+      if self.string( self.names, section.name )? == name {
+        return Ok( Some( section ) );
+      }
+    }
+    Ok( None )
+  }
+
+  // The names of the symbols defined by the library in its dynamic symbol table
+  fn dynamic_symbols( &self ) -> Result<Vec<String>, InspectError> {
+    let mut symbols = Vec::new();
+    let (entry_size, index) = if self.is_64 { (24, 6) } else { (16, 14) };
+    for section in self.sections.iter().filter( |section| section.kind == SHT_DYNSYM ) {
+      let data = self.section_data( section )?;
+      for entry in 0..data.len() as u64 / entry_size {
+        let at = section.offset + entry * entry_size;
+        if self.u16( at + index )? != SHN_UNDEF {
+          symbols.push( self.string( section.link, self.u32( at )? )?.to_string() );
+        }
+      }
+    }
+    Ok( symbols )
+  }
+}
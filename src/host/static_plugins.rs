@@ -0,0 +1,152 @@
+//! A plugin manager for plugins linked into the binary and exported with [`export_static_plugin!`](crate::export_static_plugin).
+//!
+//! Some platforms can't load shared libraries, for example fully static binaries. There the plugin crates are normal
+//! dependencies of the host, and their declarations are collected at link time. The [`StaticPluginManager`] registers them
+//! as the [`DylibPluginManager`](crate::host::DylibPluginManager) does with the libraries, and implements [`PluginManagerGet`],
+//! so the code using the plugins doesn't change.
+//! ```no_run
+//! use aanyx::import_plugin;
+//! use aanyx::host::{PluginManagerGet, StaticPluginManager};
+//! # trait MyPluginTrait { fn run( &self ); }
+//! // Link the plugin crate, which calls `export_static_plugin!`
+//! // extern crate my_plugin;
+//!
+//! let manager = StaticPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+//! if let Some( plugin ) = manager.get( &String::from("MyPlugin") ) {
+//!   plugin.run();
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use semver::{Version, VersionReq};
+
+use crate::host::dependencies::load_order;
+use crate::host::dylib::Registrations;
+use crate::host::{CollisionPolicy, LoadError, PluginManagerGet};
+use crate::plugin::{HostContext, PluginDeclaration, StaticDeclaration};
+
+// A registered declaration
+struct StaticLibrary {
+  id: String,
+  version: String,
+  shutdown: Option<unsafe extern "C" fn()>,
+}
+
+/// Registers the plugins linked into the binary with a [`StaticDeclaration`] for `PluginType`.
+///
+/// All the declarations are registered when the manager is created, after their dependencies. The `init` hooks are called
+/// after `register`, the `shutdown` hooks when the manager is dropped, in reverse order. The declarations that cannot be
+/// registered are reported by [`StaticPluginManager::errors`]: the same name registered twice, a missing dependency or a panic.
+/// The versions of rustc and aanyx are not checked, because the plugins are compiled together with the host.
+pub struct StaticPluginManager<PluginType: ?Sized + 'static> {
+  context: HostContext,
+  plugins: HashMap<String, Box<PluginType>>,
+  libraries: Vec<StaticLibrary>,
+  errors: Vec<LoadError>,
+}
+
+impl<PluginType: ?Sized + 'static> StaticPluginManager<PluginType> {
+  /// Register all the plugins linked with the declaration named `declaration`, which should be generated using
+  /// [`import_plugin!`](crate::import_plugin)
+  pub fn new( declaration: &[u8] ) -> Self {
+    Self::with_context( declaration, HostContext::new() )
+  }
+
+  /// Register all the plugins linked with the declaration named `declaration`, offering them the services of the `context`
+  pub fn with_context( declaration: &[u8], context: HostContext ) -> Self {
+    let mut declarations: Vec<&'static PluginDeclaration<PluginType>> = inventory::iter::<StaticDeclaration>.into_iter()
+      .filter( |linked| linked.symbol == declaration )
+      .filter_map( |linked| linked.declaration.downcast_ref() )
+      .collect();
+    // The order of the declarations collected by the linker is not specified
+    declarations.sort_by_key( |declaration| declaration.id.as_str() );
+
+    let mut manager = Self { context, plugins: HashMap::new(), libraries: Vec::new(), errors: Vec::new() };
+    let ids: Vec<&str> = declarations.iter().map( |declaration| declaration.id.as_str() ).collect();
+    let dependencies: Vec<Vec<&str>> = declarations.iter().map( |declaration| declaration.dependencies.as_slice().iter().map( |dependency| dependency.id.as_str() ).collect() ).collect();
+    let (order, blocked) = load_order( &ids, &dependencies );
+    let mut cycle: Vec<String> = blocked.iter().map( |&node| ids[node].to_string() ).collect();
+    cycle.sort();
+    for _ in blocked {
+      manager.errors.push( LoadError::DependencyCycle( cycle.clone() ) );
+    }
+    for node in order {
+      if let Err( error ) = manager.register( declarations[node] ) {
+        manager.errors.push( error );
+      }
+    }
+    manager
+  }
+
+  // Register the plugins of the declaration and call its init hook
+  fn register( &mut self, declaration: &'static PluginDeclaration<PluginType> ) -> Result<(), LoadError> {
+    for dependency in declaration.dependencies.as_slice() {
+      let satisfied = VersionReq::parse( dependency.version.as_str() ).is_ok_and( |requirement| {
+        self.libraries.iter().any( |library| library.id == dependency.id.as_str() && Version::parse( &library.version ).is_ok_and( |version| requirement.matches( &version ) ) )
+      });
+      if !satisfied {
+        return Err( LoadError::DependencyNotSatisfied {
+          plugin: declaration.id.to_string(),
+          dependency: dependency.id.to_string(),
+          requirement: dependency.version.to_string(),
+        });
+      }
+    }
+
+    let loaded = self.plugins.keys().map( String::as_str ).collect();
+    let mut registrations = Registrations::new( loaded, CollisionPolicy::Reject, &self.context );
+    registrations.register( declaration );
+    if let Some( message ) = registrations.panic {
+      return Err( LoadError::PluginPanicked { plugin: declaration.id.to_string(), message } );
+    }
+    if !registrations.collisions.is_empty() {
+      return Err( LoadError::NameCollision { plugin: declaration.id.to_string(), names: registrations.collisions } );
+    }
+    let registrations = registrations.plugins;
+
+    self.plugins.extend( registrations );
+    self.libraries.push( StaticLibrary { id: declaration.id.to_string(), version: declaration.version.to_string(), shutdown: declaration.shutdown } );
+    if let Some( init ) = declaration.init {
+      unsafe { init() };
+    }
+    Ok(())
+  }
+
+  /// The names of all the registered plugins
+  pub fn plugins( &self ) -> impl Iterator<Item = &str> {
+    self.plugins.keys().map( String::as_str )
+  }
+
+  /// The ids of the registered declarations, in the order they have been registered
+  pub fn libraries( &self ) -> impl Iterator<Item = &str> {
+    self.libraries.iter().map( |library| library.id.as_str() )
+  }
+
+  /// Why some of the declarations have not been registered
+  pub fn errors( &self ) -> &[LoadError] {
+    &self.errors
+  }
+
+  /// The services offered to the plugins
+  pub fn context( &self ) -> &HostContext {
+    &self.context
+  }
+}
+
+impl<PluginType: ?Sized + 'static> Drop for StaticPluginManager<PluginType> {
+  /// Call the shutdown hooks, the dependents before their dependencies, while the plugins are still alive
+  fn drop( &mut self ) {
+    for library in self.libraries.iter().rev() {
+      if let Some( shutdown ) = library.shutdown {
+        unsafe { shutdown() };
+      }
+    }
+  }
+}
+
+impl<PluginType: ?Sized + 'static> PluginManagerGet<String, PluginType> for StaticPluginManager<PluginType> {
+  fn get( &self, plugin: &String ) -> Option<&PluginType> {
+    self.plugins.get( plugin ).map( Box::as_ref )
+  }
+}
@@ -0,0 +1,141 @@
+//! Watch the files behind the loaded plugins and reload them when they change.
+//!
+//! The [`PluginWatcher`] doesn't spawn any thread: the host calls [`PluginWatcher::poll`] periodically, for example once per
+//! iteration of its main loop, and the watcher calls [`PluginManagerReload::reload`] for every file that has changed.
+//! A file is reloaded only once it has not been modified for the debounce interval, so a library that is still being
+//! written by the compiler is not loaded half-way.
+//!
+//! Libraries should be opened with [`DylibLoader::open_shadowed`](crate::host::DylibLoader::open_shadowed), so that the
+//! compiler can overwrite them while they are loaded.
+//! ```no_run
+//! use std::time::Duration;
+//! use aanyx::import_plugin;
+//! use aanyx::host::{DylibLoader, DylibPluginManager, PluginManagerLoad, PluginWatcher};
+//! # trait MyPluginTrait {}
+//!
+//! let mut manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+//! manager.load( unsafe { DylibLoader::open_shadowed( "plugins/libmy_plugin.so" ) }.unwrap() ).unwrap();
+//!
+//! let mut watcher = PluginWatcher::new( Duration::from_millis( 500 ) );
+//! watcher.watch_all( manager.libraries() );
+//! loop {
+//!   watcher.poll( &mut manager, |path, result| match result {
+//!     Ok(()) => println!( "Reloaded {}", path.display() ),
+//!     Err( error ) => println!( "Cannot reload {}: {error}", path.display() ),
+//!   });
+//!   std::thread::sleep( Duration::from_millis( 100 ) );
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::host::{PluginLoader, PluginManagerReload};
+
+// The last observed state of a file. `None` if the file could not be read.
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn stamp( path: &Path ) -> FileStamp {
+  let metadata = fs::metadata( path ).ok()?;
+  Some( (metadata.modified().ok()?, metadata.len()) )
+}
+
+struct WatchedFile {
+  stamp: FileStamp,
+  // When the last change has been observed, if it has not been reported yet
+  changed_at: Option<Instant>,
+}
+
+/// Polls the modification time of a set of files and reloads the plugins whose file has changed.
+pub struct PluginWatcher {
+  debounce: Duration,
+  files: HashMap<PathBuf, WatchedFile>,
+}
+
+impl PluginWatcher {
+  /// Create a watcher that waits `debounce` after the last write before reporting a change
+  pub fn new( debounce: Duration ) -> Self {
+    Self { debounce, files: HashMap::new() }
+  }
+
+  /// Start watching the file at `path`. Its current state is considered as already loaded.
+  pub fn watch( &mut self, path: impl Into<PathBuf> ) {
+    let path = path.into();
+    let stamp = stamp( &path );
+    self.files.insert( path, WatchedFile { stamp, changed_at: None } );
+  }
+
+  /// Watch all the `paths`, for example the libraries of a [`DylibPluginManager`](crate::host::DylibPluginManager)
+  pub fn watch_all<P: Into<PathBuf>>( &mut self, paths: impl IntoIterator<Item = P> ) {
+    for path in paths {
+      self.watch( path );
+    }
+  }
+
+  /// Stop watching the file at `path`. Returns false if the file was not watched.
+  pub fn unwatch( &mut self, path: &Path ) -> bool {
+    self.files.remove( path ).is_some()
+  }
+
+  /// The watched files
+  pub fn watched( &self ) -> impl Iterator<Item = &Path> {
+    self.files.keys().map( PathBuf::as_path )
+  }
+
+  /// Return the files that have changed and have not been modified for the debounce interval.
+  /// Every change is returned only once.
+  pub fn changed( &mut self ) -> Vec<PathBuf> {
+    let now = Instant::now();
+    let mut changed = Vec::new();
+    for (path, file) in self.files.iter_mut() {
+      let current = stamp( path );
+      if current != file.stamp {
+        file.stamp = current;
+        file.changed_at = Some( now );
+      } else if file.stamp.is_some() && file.changed_at.is_some_and( |at| now.duration_since( at ) >= self.debounce ) {
+        file.changed_at = None;
+        changed.push( path.clone() );
+      }
+    }
+    changed.sort();
+    changed
+  }
+
+  /// Call [`PluginManagerReload::reload`] for every changed file and report the result to `on_reload`
+  pub fn poll<Manager, PluginType, ManagerReloadError>( &mut self, manager: &mut Manager, mut on_reload: impl FnMut( &Path, Result<(), ManagerReloadError> ) )
+  where Manager: PluginManagerReload<PathBuf, PluginType, ManagerReloadError> {
+    for path in self.changed() {
+      on_reload( &path, manager.reload( &path ) );
+    }
+  }
+
+  /// Call [`PluginManagerReload::reload_as_new`] for every changed file, using `new_plugin` to create the loader of the file,
+  /// and report the result to `on_reload`
+  /// ```no_run
+  /// # use std::time::Duration;
+  /// # use aanyx::import_plugin;
+  /// # use aanyx::host::{DylibLoader, DylibPluginManager, PluginWatcher};
+  /// # trait MyPluginTrait {}
+  /// # let mut manager = DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+  /// # let mut watcher = PluginWatcher::new( Duration::from_millis( 500 ) );
+  /// watcher.poll_as_new( &mut manager, |path| unsafe { DylibLoader::open( path ) }, |path, result| {
+  ///   if let Err( error ) = result {
+  ///     println!( "Cannot reload {}: {error}", path.display() );
+  ///   }
+  /// });
+  /// ```
+  pub fn poll_as_new<Manager, Loader, PluginType, ManagerReloadError>(
+    &mut self,
+    manager: &mut Manager,
+    mut new_plugin: impl FnMut( &Path ) -> Result<Loader, ManagerReloadError>,
+    mut on_reload: impl FnMut( &Path, Result<(), ManagerReloadError> )
+  )
+  where Manager: PluginManagerReload<PathBuf, PluginType, ManagerReloadError>, Loader: PluginLoader<PathBuf, PluginType> {
+    for path in self.changed() {
+      let result = new_plugin( &path ).and_then( |loader| manager.reload_as_new( loader, &path ) );
+      on_reload( &path, result );
+    }
+  }
+}
@@ -13,8 +13,176 @@ pub mod system;
 pub mod pdk;
 
 /// Exports all the requirements to make a plugin compatible with the host.
-/// Please see the `Limitations` section inoerder to understan what isor isnot possible to do and how to solve some problems.
+/// Please see the `Mangling` section of `import_plugin!` in order to understand how the host and the plugins agree on the plugin type.
 pub mod plugin;
 
+/// Types with a stable layout, used by the plugin interfaces that don't depend on the version of rustc.
+pub mod abi;
+
 /// Constains allthe traits that the plugin system should support. 
-pub mod host;
\ No newline at end of file
+pub mod host;
+
+/// Export a plugin without writing the `register` function by hand. It's an alternative to [`export_plugin!`].
+///
+/// On an `impl Trait for Type` block it exports the plugin as `dyn Trait`, built with `Type::default()` and registered with the
+/// name of the type. The same attribute can be used on an `impl Type` block to export `Type` itself.
+/// ```
+/// # pub mod api { pub trait Greeter { fn greet( &self, name: &str ) -> String; } }
+/// use api::Greeter;
+///
+/// #[derive(Default)]
+/// struct English;
+///
+/// #[aanyx::plugin]
+/// impl Greeter for English {
+///   fn greet( &self, name: &str ) -> String { format!( "Hello {name}" ) }
+/// }
+/// # assert_eq!( aanyx::import_plugin!( dyn Greeter ), b"plugin_declaration_dyn_Greeter" );
+/// # let _ = &plugin_declaration_dyn_Greeter;
+/// ```
+///
+/// On a function without arguments it registers the returned plugin with the name of the function. The plugin type is
+/// the returned type, a `Box<dyn Trait>` or the type given as first argument of the attribute:
+/// ```
+/// # pub mod api { pub trait Greeter { fn greet( &self, name: &str ) -> String; } }
+/// use api::Greeter;
+///
+/// struct Italian { greeting: String }
+/// impl Greeter for Italian {
+///   fn greet( &self, name: &str ) -> String { format!( "{} {name}", self.greeting ) }
+/// }
+///
+/// #[aanyx::plugin( dyn Greeter )]
+/// fn italian() -> Italian {
+///   Italian { greeting: String::from( "Ciao" ) }
+/// }
+/// ```
+///
+/// The attribute accepts the same options of [`export_plugin!`], plus `name` to change the name used to register the plugin:
+/// ```
+/// # pub mod api { pub trait Greeter { fn greet( &self, name: &str ) -> String; } }
+/// use api::Greeter;
+/// # #[derive(Default)] struct English;
+/// extern "C" fn init() {}
+///
+/// #[aanyx::plugin( name = "english", id = "greetings", init = init )]
+/// impl Greeter for English {
+///   fn greet( &self, name: &str ) -> String { format!( "Hello {name}" ) }
+/// }
+/// ```
+///
+/// Only one plugin per type can be exported by a library, because the declaration has a fixed name.
+/// To register many plugins of the same type, use [`export_plugin!`].
+///
+/// ## Errors
+/// The attribute reports the misuses at compile time, for example unknown options:
+/// ```compile_fail
+/// # pub trait Greeter {}
+/// # #[derive(Default)] struct English;
+/// #[aanyx::plugin( nmae = "english" )]
+/// impl Greeter for English {}
+/// ```
+/// a plugin type that contradicts the impl block:
+/// ```compile_fail
+/// # pub trait Greeter {}
+/// # pub trait Other {}
+/// # #[derive(Default)] struct English;
+/// #[aanyx::plugin( dyn Other )]
+/// impl Greeter for English {}
+/// ```
+/// or a constructor that takes arguments:
+/// ```compile_fail
+/// # pub trait Greeter {}
+/// # struct English;
+/// # impl Greeter for English {}
+/// #[aanyx::plugin( dyn Greeter )]
+/// fn english( greeting: String ) -> English { English }
+/// ```
+pub use aanyx_macros::plugin;
+
+/// Implement [`Interface`](plugin::Interface) for the trait object of a trait, with a fingerprint of the signatures of its items.
+///
+/// The fingerprint changes when a method is added, removed, renamed or when the types of its arguments change,
+/// while the names of the arguments, the attributes and the default bodies are ignored.
+/// The plugin exports the fingerprint with the `fingerprint` option of [`export_plugin!`] and the host checks it,
+/// see [`DylibPluginManager::with_fingerprint`](host::DylibPluginManager::with_fingerprint).
+/// ```
+/// use aanyx::plugin::Interface;
+///
+/// mod v1 {
+///   #[aanyx::interface]
+///   pub trait Greeter {
+///     fn greet( &self, name: &str ) -> String;
+///   }
+/// }
+///
+/// mod v2 {
+///   #[aanyx::interface]
+///   pub trait Greeter {
+///     fn greet( &self, name: &str, times: usize ) -> String;
+///   }
+/// }
+///
+/// mod renamed {
+///   #[aanyx::interface]
+///   pub trait Greeter {
+///     /// The arguments names and the docs don't matter
+///     fn greet( &self, who: &str ) -> String;
+///   }
+/// }
+///
+/// assert_ne!( <dyn v1::Greeter as Interface>::FINGERPRINT, <dyn v2::Greeter as Interface>::FINGERPRINT );
+/// assert_eq!( <dyn v1::Greeter as Interface>::FINGERPRINT, <dyn renamed::Greeter as Interface>::FINGERPRINT );
+/// ```
+pub use aanyx_macros::interface;
+
+/// Make a plugin trait usable by a host and a plugin compiled with different versions of rustc.
+///
+/// The attribute generates `<Trait>Ffi`, a `#[repr(C)]` object made of a pointer to the plugin and a vtable of `extern "C"`
+/// functions, which implements the trait. The arguments are converted to the types of [`abi`], so the methods can only take
+/// `&self` or `&mut self` and arguments that are primitives, `&str`, `String` or slices of primitives, and return
+/// primitives or `String`.
+///
+/// The plugin exports the trait as usual adding the `stable_abi = true` option to [`export_plugin!`], while the host calls
+/// [`DylibPluginManager::with_stable_abi`](host::DylibPluginManager::with_stable_abi). Then the plugins are registered through
+/// the stable objects and the host doesn't check the version of rustc. The versions of aanyx are still checked.
+/// ```
+/// use aanyx::{export_plugin, import_plugin, plugin::PluginRegistrar};
+/// use aanyx::host::DylibPluginManager;
+///
+/// #[aanyx::stable_abi]
+/// pub trait Calculator {
+///   fn add( &self, a: i64, b: i64 ) -> i64;
+///   fn describe( &self, name: &str ) -> String;
+/// }
+///
+/// // In the plugin
+/// struct Simple;
+/// impl Calculator for Simple {
+///   fn add( &self, a: i64, b: i64 ) -> i64 { a + b }
+///   fn describe( &self, name: &str ) -> String { format!( "{name} adds numbers" ) }
+/// }
+///
+/// #[allow(improper_ctypes_definitions)]
+/// extern "C" fn register( registrar: &mut dyn PluginRegistrar<dyn Calculator> ) {
+///   let _ = registrar.register_plugin( "simple", Box::new( Simple ) );
+/// }
+/// export_plugin!( register, dyn Calculator, stable_abi = true );
+///
+/// // In the host
+/// let manager = DylibPluginManager::<dyn Calculator>::new( import_plugin!( dyn Calculator ) ).with_stable_abi();
+///
+/// // The stable object can be used directly too
+/// let calculator = CalculatorFfi::new( Box::new( Simple ) );
+/// assert_eq!( calculator.add( 2, 3 ), 5 );
+/// assert_eq!( calculator.describe( "simple" ), "simple adds numbers" );
+/// ```
+///
+/// Other types are refused at compile time:
+/// ```compile_fail
+/// #[aanyx::stable_abi]
+/// pub trait Calculator {
+///   fn add( &self, values: Vec<i64> ) -> i64;
+/// }
+/// ```
+pub use aanyx_macros::stable_abi;
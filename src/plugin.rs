@@ -2,16 +2,555 @@
 #[doc(hidden)]
 pub use paste as plugin_paste;
 
+// The macros computing the names of the declarations, used by `export_plugin!` and `import_plugin!`
+#[doc(hidden)]
+pub use aanyx_macros::{__export_declaration, __export_static_declaration, __plugin_symbol};
+
+// Re-export of the inventory crate, used by `export_static_plugin!` to collect the declarations at link time
+#[doc(hidden)]
+pub use inventory as plugin_inventory;
+
+use std::any::{type_name, Any};
+use std::error::Error;
+use std::fmt;
+
+use crate::abi::{RSlice, RStr, StableInterface, StableRegistrar};
+
+// The version of the layout of `PluginDeclaration`, increased every time its fields change
+#[doc(hidden)]
+pub const DECLARATION_VERSION: u32 = 2;
+
+// The layout is `repr(C)` so that the version fields are always at the beginning of the declaration.
+// The host reads `nyx_version` and `declaration_version` first, and reads the other fields only if the layout is the one it knows.
+// The fields read before the version of rustc is trusted, which is never checked for the plugins with a stable ABI, use the
+// types of `abi` instead of `&str` and `&[T]`, whose layout is decided by the compiler.
 #[doc(hidden)]
 #[derive(Clone, Copy)]
+#[repr(C)]
+#[allow(improper_ctypes_definitions)]
 pub struct PluginDeclaration<PluginType: ?Sized> {
-  pub rustc_version: &'static str,
-  pub nyx_version: &'static str,
+  pub rustc_version: RStr<'static>,
+  pub nyx_version: RStr<'static>,
+  pub declaration_version: u32,
   pub register: unsafe extern "C" fn(&mut dyn PluginRegistrar<PluginType>),
+  pub id: RStr<'static>,
+  pub version: RStr<'static>,
+  pub dependencies: RSlice<'static, PluginDependency>,
+  pub init: Option<unsafe extern "C" fn()>,
+  pub shutdown: Option<unsafe extern "C" fn()>,
+  pub before_reload: Option<unsafe extern "C" fn()>,
+  pub state_version: u32,
+  pub export_state: Option<unsafe extern "C" fn() -> Vec<u8>>,
+  pub import_state: Option<unsafe extern "C" fn(PluginState) -> Result<(), String>>,
+  pub fingerprint: u64,
+  pub stable_register: Option<unsafe extern "C" fn(&mut StableRegistrar, &PluginDeclaration<PluginType>)>,
+  pub metadata: MetadataDeclaration,
+  pub try_register: Option<unsafe extern "C" fn(&mut dyn PluginRegistrar<PluginType>) -> Result<(), String>>,
+}
+
+#[allow(improper_ctypes_definitions)]
+impl<PluginType: ?Sized> PluginDeclaration<PluginType> {
+  /// Create the declaration of the plugin `id` at the given `version`, compiled with the current versions of rustc and aanyx
+  pub const fn new( register: unsafe extern "C" fn(&mut dyn PluginRegistrar<PluginType>), id: &'static str, version: &'static str ) -> Self {
+    Self {
+      rustc_version: RStr::new( crate::RUSTC_VERSION ),
+      nyx_version: RStr::new( crate::CORE_VERSION ),
+      declaration_version: DECLARATION_VERSION,
+      register,
+      id: RStr::new( id ),
+      version: RStr::new( version ),
+      dependencies: RSlice::new( &[] ),
+      init: None,
+      shutdown: None,
+      before_reload: None,
+      state_version: 0,
+      export_state: None,
+      import_state: None,
+      fingerprint: 0,
+      stable_register: None,
+      metadata: MetadataDeclaration::new( id, version ),
+      try_register: None,
+    }
+  }
+
+  pub const fn with_id( mut self, id: &'static str ) -> Self {
+    self.id = RStr::new( id );
+    self
+  }
+
+  pub const fn with_version( mut self, version: &'static str ) -> Self {
+    self.version = RStr::new( version );
+    self
+  }
+
+  pub const fn with_dependencies( mut self, dependencies: &'static [PluginDependency] ) -> Self {
+    self.dependencies = RSlice::new( dependencies );
+    self
+  }
+
+  pub const fn with_init( mut self, init: unsafe extern "C" fn() ) -> Self {
+    self.init = Some( init );
+    self
+  }
+
+  pub const fn with_shutdown( mut self, shutdown: unsafe extern "C" fn() ) -> Self {
+    self.shutdown = Some( shutdown );
+    self
+  }
+
+  pub const fn with_before_reload( mut self, before_reload: unsafe extern "C" fn() ) -> Self {
+    self.before_reload = Some( before_reload );
+    self
+  }
+
+  pub const fn with_state_version( mut self, state_version: u32 ) -> Self {
+    self.state_version = state_version;
+    self
+  }
+
+  pub const fn with_export_state( mut self, export_state: unsafe extern "C" fn() -> Vec<u8> ) -> Self {
+    self.export_state = Some( export_state );
+    self
+  }
+
+  pub const fn with_import_state( mut self, import_state: unsafe extern "C" fn(PluginState) -> Result<(), String> ) -> Self {
+    self.import_state = Some( import_state );
+    self
+  }
+
+  pub const fn with_fingerprint( mut self, fingerprint: u64 ) -> Self {
+    self.fingerprint = fingerprint;
+    self
+  }
+
+  pub const fn with_metadata( mut self, metadata: MetadataDeclaration ) -> Self {
+    self.metadata = metadata;
+    self
+  }
+
+  pub const fn with_try_register( mut self, try_register: unsafe extern "C" fn(&mut dyn PluginRegistrar<PluginType>) -> Result<(), String> ) -> Self {
+    self.try_register = Some( try_register );
+    self
+  }
+}
+
+impl<PluginType: StableInterface + ?Sized> PluginDeclaration<PluginType> {
+  pub const fn with_stable_abi( mut self, stable: bool ) -> Self {
+    self.stable_register = if stable { Some( stable_register::<PluginType> ) } else { None };
+    self
+  }
+}
+
+// Called by the hosts using the stable ABI in place of `register`: the plugins registered by `register` are forwarded to the host
+unsafe extern "C" fn stable_register<PluginType: StableInterface + ?Sized>( registrar: &mut StableRegistrar, declaration: &PluginDeclaration<PluginType> ) {
+  match declaration.try_register {
+    Some( try_register ) => {
+      if let Err( message ) = unsafe { try_register( &mut StableAdapter { registrar } ) } {
+        registrar.report_panic( &message );
+      }
+    }
+    None => unsafe { (declaration.register)( &mut StableAdapter { registrar } ) },
+  }
+}
+
+struct StableAdapter<'r> {
+  registrar: &'r mut StableRegistrar,
+}
+
+impl<PluginType: StableInterface + ?Sized> PluginRegistrar<PluginType> for StableAdapter<'_> {
+  fn register_plugin( &mut self, name: &str, plugin: Box<PluginType> ) -> Result<(), RegisterError> {
+    self.registrar.register_plugin( name, plugin )
+  }
+}
+
+// Run the `register` function of a plugin, turning a panic into its message. Used by `export_plugin!`.
+#[doc(hidden)]
+pub fn catch_panic( register: impl FnOnce() ) -> Result<(), String> {
+  std::panic::catch_unwind( std::panic::AssertUnwindSafe( register ) ).map_err( |payload| {
+    match payload.downcast::<String>() {
+      Ok( message ) => *message,
+      Err( payload ) => payload.downcast_ref::<&str>().map_or( "the plugin panicked", |message| message ).to_string(),
+    }
+  })
+}
+
+/// A declaration linked into the binary with [`export_static_plugin!`](crate::export_static_plugin) and collected by the
+/// [`StaticPluginManager`](crate::host::StaticPluginManager).
+/// The type of the declaration is erased, the manager finds its declarations by `symbol`, the name generated by
+/// [`import_plugin!`](crate::import_plugin), and then checks their type.
+pub struct StaticDeclaration {
+  pub symbol: &'static [u8],
+  pub declaration: &'static ( dyn Any + Send + Sync ),
+}
+
+impl StaticDeclaration {
+  pub const fn new( symbol: &'static [u8], declaration: &'static ( dyn Any + Send + Sync ) ) -> Self {
+    Self { symbol, declaration }
+  }
+}
+
+inventory::collect!( StaticDeclaration );
+
+/// A trait object whose interface has a fingerprint, usually implemented with the [`interface`](crate::interface) attribute.
+/// The fingerprint changes whenever the methods of the trait change, so a host can refuse plugins compiled against another
+/// version of the trait, see [`DylibPluginManager::with_fingerprint`](crate::host::DylibPluginManager::with_fingerprint).
+pub trait Interface {
+  const FINGERPRINT: u64;
 }
 
+/// The fingerprint of an interface described by a string, for example a version chosen by the developer.
+/// It's the 64 bits FNV-1a hash of the string.
+/// ```
+/// use aanyx::plugin::fingerprint;
+///
+/// const GREETER_V2: u64 = fingerprint( "greeter 2" );
+/// assert_ne!( GREETER_V2, fingerprint( "greeter 1" ) );
+/// ```
+pub const fn fingerprint( interface: &str ) -> u64 {
+  let bytes = interface.as_bytes();
+  let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+  let mut index = 0;
+  while index < bytes.len() {
+    hash ^= bytes[index] as u64;
+    hash = hash.wrapping_mul( 0x0000_0100_0000_01b3 );
+    index += 1;
+  }
+  hash
+}
+
+/// The state handed by a library to its new build when it is reloaded.
+/// `version` is the `state_version` declared by the library that exported the state, so that the new build can
+/// migrate the `data` produced by an older build, or reject it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginState {
+  pub version: u32,
+  pub data: Vec<u8>,
+}
+
+/// A plugin that must be loaded before the plugin declaring the dependency.
+/// The `version` is a semver requirement, like the ones used by cargo.
+/// ```
+/// use aanyx::plugin::PluginDependency;
+///
+/// const STORAGE: PluginDependency = PluginDependency::new( "storage", "^1.2" );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct PluginDependency {
+  pub id: RStr<'static>,
+  pub version: RStr<'static>,
+}
+
+impl PluginDependency {
+  pub const fn new( id: &'static str, version: &'static str ) -> Self {
+    Self { id: RStr::new( id ), version: RStr::new( version ) }
+  }
+}
+
+/// The description of a library, written by [`plugin_metadata!`](crate::plugin_metadata) and read by the host as [`PluginMetadata`].
+/// `authors` are separated by `:`, like in the `CARGO_PKG_AUTHORS` variable set by cargo.
+/// ```
+/// use aanyx::abi::RStr;
+/// use aanyx::plugin::{MetadataDeclaration, PluginMetadata};
+///
+/// const METADATA: MetadataDeclaration = MetadataDeclaration::new( "greeter", "1.0.0" )
+///   .with_authors( "Alice:Bob" )
+///   .with_capabilities( &[RStr::new( "greet" )] );
+///
+/// let metadata = PluginMetadata::from( &METADATA );
+/// assert_eq!( metadata.authors, ["Alice", "Bob"] );
+/// assert!( metadata.description.is_empty() );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct MetadataDeclaration {
+  pub name: RStr<'static>,
+  pub version: RStr<'static>,
+  pub authors: RStr<'static>,
+  pub description: RStr<'static>,
+  pub license: RStr<'static>,
+  pub capabilities: RSlice<'static, RStr<'static>>,
+}
+
+impl MetadataDeclaration {
+  pub const fn new( name: &'static str, version: &'static str ) -> Self {
+    Self {
+      name: RStr::new( name ),
+      version: RStr::new( version ),
+      authors: RStr::new( "" ),
+      description: RStr::new( "" ),
+      license: RStr::new( "" ),
+      capabilities: RSlice::new( &[] ),
+    }
+  }
+
+  pub const fn with_name( mut self, name: &'static str ) -> Self {
+    self.name = RStr::new( name );
+    self
+  }
+
+  pub const fn with_version( mut self, version: &'static str ) -> Self {
+    self.version = RStr::new( version );
+    self
+  }
+
+  pub const fn with_authors( mut self, authors: &'static str ) -> Self {
+    self.authors = RStr::new( authors );
+    self
+  }
+
+  pub const fn with_description( mut self, description: &'static str ) -> Self {
+    self.description = RStr::new( description );
+    self
+  }
+
+  pub const fn with_license( mut self, license: &'static str ) -> Self {
+    self.license = RStr::new( license );
+    self
+  }
+
+  /// The capabilities are stored with a stable layout, so they are written with [`RStr::new`]
+  pub const fn with_capabilities( mut self, capabilities: &'static [RStr<'static>] ) -> Self {
+    self.capabilities = RSlice::new( capabilities );
+    self
+  }
+}
+
+// The metadata is also copied in the `.aanyx_metadata` section of the library, so that it can be read without opening the
+// library, see `host::inspect`. Each declaration appends a record made of the version of its format, the length of the rest
+// of the record, the symbol of the declaration and the fields of the metadata. The strings are prefixed by their length and
+// the capabilities by their count, all the numbers but the version are `u32` little endian. The version is never 0, so that
+// the padding added by the linker between the records can be skipped.
+#[doc(hidden)]
+pub const METADATA_RECORD_VERSION: u8 = 1;
+
+#[doc(hidden)]
+pub const METADATA_SECTION: &str = ".aanyx_metadata";
+
+#[doc(hidden)]
+pub const fn metadata_record_len( symbol: &str, metadata: &MetadataDeclaration ) -> usize {
+  let mut len = 1 + 4 + 4 + symbol.len() + 4 + metadata.name.as_str().len() + 4 + metadata.version.as_str().len()
+    + 4 + metadata.authors.as_str().len() + 4 + metadata.description.as_str().len() + 4 + metadata.license.as_str().len() + 4;
+  let capabilities = metadata.capabilities.as_slice();
+  let mut index = 0;
+  while index < capabilities.len() {
+    len += 4 + capabilities[index].as_str().len();
+    index += 1;
+  }
+  len
+}
+
+#[doc(hidden)]
+pub const fn metadata_record<const LEN: usize>( symbol: &str, metadata: &MetadataDeclaration ) -> [u8; LEN] {
+  let mut record = [0; LEN];
+  record[0] = METADATA_RECORD_VERSION;
+  let mut at = write_u32( &mut record, 1, ( LEN - 5 ) as u32 );
+  at = write_str( &mut record, at, symbol );
+  at = write_str( &mut record, at, metadata.name.as_str() );
+  at = write_str( &mut record, at, metadata.version.as_str() );
+  at = write_str( &mut record, at, metadata.authors.as_str() );
+  at = write_str( &mut record, at, metadata.description.as_str() );
+  at = write_str( &mut record, at, metadata.license.as_str() );
+  let capabilities = metadata.capabilities.as_slice();
+  at = write_u32( &mut record, at, capabilities.len() as u32 );
+  let mut index = 0;
+  while index < capabilities.len() {
+    at = write_str( &mut record, at, capabilities[index].as_str() );
+    index += 1;
+  }
+  assert!( at == LEN, "the length of the record must be computed by `metadata_record_len`" );
+  record
+}
+
+const fn write_u32( record: &mut [u8], at: usize, value: u32 ) -> usize {
+  write_bytes( record, at, &value.to_le_bytes() )
+}
+
+const fn write_str( record: &mut [u8], at: usize, value: &str ) -> usize {
+  let at = write_u32( record, at, value.len() as u32 );
+  write_bytes( record, at, value.as_bytes() )
+}
+
+const fn write_bytes( record: &mut [u8], at: usize, bytes: &[u8] ) -> usize {
+  let mut index = 0;
+  while index < bytes.len() {
+    record[at + index] = bytes[index];
+    index += 1;
+  }
+  at + bytes.len()
+}
+
+/// The description of a library read by the host before registering its plugins,
+/// see [`DylibPluginManager::metadata`](crate::host::DylibPluginManager::metadata).
+/// It doesn't borrow from the library, so it can be kept after the library has been unloaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginMetadata {
+  pub name: String,
+  pub version: String,
+  pub authors: Vec<String>,
+  pub description: String,
+  pub license: String,
+  /// What the plugins of the library can do, with names chosen by the host
+  pub capabilities: Vec<String>,
+}
+
+impl From<&MetadataDeclaration> for PluginMetadata {
+  fn from( metadata: &MetadataDeclaration ) -> Self {
+    Self {
+      name: metadata.name.to_string(),
+      version: metadata.version.to_string(),
+      authors: metadata.authors.as_str().split( ':' ).filter( |author| !author.is_empty() ).map( str::to_string ).collect(),
+      description: metadata.description.to_string(),
+      license: metadata.license.to_string(),
+      capabilities: metadata.capabilities.as_slice().iter().map( |capability| capability.to_string() ).collect(),
+    }
+  }
+}
+
+/// The reasons why a plugin could not be registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterError {
+  /// A plugin with the same name has already been registered by this library or loaded from another one
+  NameTaken( String ),
+}
+
+impl fmt::Display for RegisterError {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    match self {
+      Self::NameTaken( name ) => write!( f, "a plugin named {name} already exists" ),
+    }
+  }
+}
+
+impl Error for RegisterError {}
+
 pub trait PluginRegistrar<PluginType: ?Sized> {
-  fn register_plugin(&mut self, name: &str, plugin: Box<PluginType>);
+  /// Register the `plugin` with the given `name`. What happens when the name is already taken depends on the host,
+  /// which may reject the plugin, replace the existing one or register it under another name.
+  fn register_plugin(&mut self, name: &str, plugin: Box<PluginType>) -> Result<(), RegisterError>;
+
+  /// The services offered by the host. Empty if the host doesn't offer any.
+  fn context(&self) -> &HostContext {
+    &EMPTY_CONTEXT
+  }
+}
+
+static EMPTY_CONTEXT: HostContext = HostContext::new();
+
+/// The services offered by the host to the plugins while they register, like a logger, the configuration or a place to
+/// register callbacks. Each service has a name and a type, and the plugin must ask for both.
+/// ```
+/// use std::sync::Mutex;
+/// use aanyx::{ export_plugin, plugin::{HostContext, PluginRegistrar}};
+/// # pub trait MyPluginTrait {}
+/// # struct MyPlugin;
+/// # impl MyPluginTrait for MyPlugin {}
+///
+/// // In the host
+/// let context = HostContext::new()
+///   .with_service( "log", Box::new( |message: &str| println!( "{message}" ) ) as Box<dyn Fn(&str) + Send + Sync> )
+///   .with_service( "commands", Mutex::new( Vec::<String>::new() ) );
+///
+/// // In the plugin
+/// fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
+///   let context = registrar.context();
+///   // SAFETY: the types of the services are defined by std, which is the same for the host and the plugin
+///   if let Some( log ) = unsafe { context.service::<Box<dyn Fn(&str) + Send + Sync>>( "log" ) } {
+///     log( "registering MyPlugin" );
+///   }
+///   if let Some( commands ) = unsafe { context.service::<Mutex<Vec<String>>>( "commands" ) } {
+///     commands.lock().unwrap().push( String::from("my-command") );
+///   }
+///   let _ = registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
+/// }
+/// export_plugin!( register, dyn MyPluginTrait );
+/// ```
+///
+/// ## Versions
+/// Like the [`PluginDeclaration`], the context starts with the versions of rustc and aanyx used by the host, so that a plugin can
+/// tell whether it can use the services: [`HostContext::service`] only returns them if the plugin has been compiled with the same
+/// rustc and a semver compatible version of aanyx.
+///
+/// The services are identified by the name of their type, because the [`TypeId`](std::any::TypeId) of a type may change between the
+/// builds of the host and of the plugin. So the types must be defined by a crate shared by both, in the same version.
+#[repr(C)]
+pub struct HostContext {
+  pub rustc_version: &'static str,
+  pub nyx_version: &'static str,
+  services: Vec<(String, &'static str, Box<dyn Any + Send + Sync>)>,
+}
+
+impl HostContext {
+  /// Create a context without services, for the current versions of rustc and aanyx
+  pub const fn new() -> Self {
+    Self { rustc_version: crate::RUSTC_VERSION, nyx_version: crate::CORE_VERSION, services: Vec::new() }
+  }
+
+  /// Offer the `service` to the plugins with the given `name`, replacing the service with the same name, if any
+  pub fn with_service<Service: Any + Send + Sync>( mut self, name: impl Into<String>, service: Service ) -> Self {
+    self.insert_service( name, service );
+    self
+  }
+
+  /// Offer the `service` to the plugins with the given `name`, replacing the service with the same name, if any
+  pub fn insert_service<Service: Any + Send + Sync>( &mut self, name: impl Into<String>, service: Service ) {
+    let name = name.into();
+    self.services.retain( |(other, _, _)| *other != name );
+    self.services.push( (name, type_name::<Service>(), Box::new( service )) );
+  }
+
+  /// Remove the service with the given `name`, returning true if it existed
+  pub fn remove_service( &mut self, name: &str ) -> bool {
+    let len = self.services.len();
+    self.services.retain( |(other, _, _)| other != name );
+    self.services.len() != len
+  }
+
+  /// The service with the given `name`, if it exists, has type `Service` and the context is compatible with the caller.
+  ///
+  /// ## Safety
+  /// The type of the service is only checked by its name, so the caller must make sure that the type named like `Service`
+  /// in the host is `Service`: it must be defined by the same version of the same crate, and not by two crates with the
+  /// same name, like two versions of a crate in the dependencies of the host.
+  pub unsafe fn service<Service: Any + Send + Sync>( &self, name: &str ) -> Option<&Service> {
+    if !self.is_compatible() {
+      return None;
+    }
+    let (_, type_name, service) = self.services.iter().find( |(other, _, _)| other == name )?;
+    if *type_name != std::any::type_name::<Service>() {
+      return None;
+    }
+    // SAFETY: the types have the same name, so they are the same type as guaranteed by the caller, and the caller has been
+    // compiled with the same rustc of the host
+    Some( unsafe { &*( service.as_ref() as *const (dyn Any + Send + Sync) as *const Service ) } )
+  }
+
+  /// The names of all the services
+  pub fn services( &self ) -> impl Iterator<Item = &str> {
+    self.services.iter().map( |(name, _, _)| name.as_str() )
+  }
+
+  /// True if the context has been created with the same rustc and a semver compatible version of aanyx of the caller
+  pub fn is_compatible( &self ) -> bool {
+    use crate::host::CompatibilityPolicy;
+    self.rustc_version == crate::RUSTC_VERSION && crate::host::SemverCompatible.is_compatible( self.nyx_version, crate::CORE_VERSION )
+  }
+}
+
+impl Default for HostContext {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl fmt::Debug for HostContext {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    f.debug_struct( "HostContext" )
+      .field( "rustc_version", &self.rustc_version )
+      .field( "nyx_version", &self.nyx_version )
+      .field( "services", &self.services().collect::<Vec<_>>() )
+      .finish()
+  }
 }
 
 /// This macro automatically creates all the components that are required by the app to load the plugin and check the compatibility
@@ -25,7 +564,7 @@ pub trait PluginRegistrar<PluginType: ?Sized> {
 /// 
 /// #[allow(improper_ctypes_definitions)]
 /// extern "C" fn register(registrar: &mut dyn PluginRegistrar< MyPlugin >) {
-///   registrar.register_plugin("MyPlugin", Box::new( MyPlugin {} ));
+///   let _ = registrar.register_plugin("MyPlugin", Box::new( MyPlugin {} ));
 /// }
 /// export_plugin!( register, MyPlugin );
 /// ```
@@ -47,39 +586,272 @@ pub trait PluginRegistrar<PluginType: ?Sized> {
 /// 
 /// #[allow(improper_ctypes_definitions)]
 /// extern "C" fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
-///   registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
+///   let _ = registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
 /// }
 /// export_plugin!( register, dyn MyPluginTrait );
 /// ```
-/// 
-/// ## Limitations
-/// See [`import_plugin's limitations`](crate::import_plugin#limtations).
+///
+/// ## Panics
+/// The `register` function is called inside [`catch_unwind`](std::panic::catch_unwind), so a panic is reported to the host,
+/// which rejects the library and keeps running, see [`LoadError::PluginPanicked`](crate::host::LoadError::PluginPanicked).
+/// A panic cannot leave an `extern "C"` function without aborting, so `register` should be a plain Rust function for this to work:
+/// ```
+/// use aanyx::{ export_plugin, plugin::PluginRegistrar};
+/// # pub trait MyPluginTrait {}
+/// # struct MyPlugin;
+/// # impl MyPluginTrait for MyPlugin {}
+///
+/// fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
+///   let name = std::env::var( "MY_PLUGIN_NAME" ).expect( "MY_PLUGIN_NAME is not set" );
+///   let _ = registrar.register_plugin(&name, Box::new(MyPlugin));
+/// }
+/// export_plugin!( register, dyn MyPluginTrait );
+/// ```
+///
+/// ## Options
+/// The declaration can be customized adding `option = value` pairs after the plugin type:
+/// * `id`: the identifier of the plugin used to resolve dependencies. Defaults to the name of the crate
+/// * `version`: the version of the plugin. Defaults to the version of the crate
+/// * `dependencies`: a list of [`PluginDependency`] that must be loaded before this plugin
+/// * `init`: an `extern "C" fn()` called by the host after `register`
+/// * `shutdown`: an `extern "C" fn()` called by the host before the library is unloaded. The plugins registered by the
+///   library are still alive, so this is the place to stop their threads and flush their state
+/// * `before_reload`: an `extern "C" fn()` called by the host before the library is reloaded, followed by `shutdown`
+/// * `state_version`: the version of the format of the state exported by `export_state`. Defaults to 0
+/// * `export_state`: an `extern "C" fn() -> Vec<u8>` called by the host before the library is reloaded, after `before_reload`.
+///   The returned data is handed to the `import_state` of the new build
+/// * `import_state`: an `extern "C" fn(PluginState) -> Result<(), String>` called by the host after the new build has been
+///   registered and before its `init`. It can migrate the state of an older `state_version`, or return an error to start afresh
+/// * `fingerprint`: the fingerprint of the plugin interface, checked by the host before calling `register`.
+///   Use `<dyn MyPluginTrait as Interface>::FINGERPRINT` with the [`interface`](crate::interface) attribute, or [`fingerprint`]
+/// * `stable_abi`: `true` to let the host load the plugin even if it has been compiled with another version of rustc.
+///   The trait must be declared with [`stable_abi`](crate::stable_abi) and the state hooks are not called
+/// * `metadata`: a [`MetadataDeclaration`] the host can read without registering the plugins. Defaults to [`plugin_metadata!`](crate::plugin_metadata)
+/// * `static_feature`: the feature of the plugin crate that links the plugin statically, see [below](#static-or-dynamic).
+///   It must be the first option
+/// ```
+/// use aanyx::{ export_plugin, plugin::{PluginDependency, PluginRegistrar}};
+/// # pub trait MyPluginTrait {}
+/// # struct MyPlugin;
+/// # impl MyPluginTrait for MyPlugin {}
+///
+/// #[allow(improper_ctypes_definitions)]
+/// extern "C" fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
+///   let _ = registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
+/// }
+/// extern "C" fn shutdown() {
+///   // Join threads, flush files...
+/// }
+///
+/// export_plugin!( register, dyn MyPluginTrait,
+///   id = "my-plugin",
+///   dependencies = &[ PluginDependency::new( "storage", "^1.2" ) ],
+///   shutdown = shutdown,
+/// );
+/// ```
+///
+/// Describing the library to the host:
+/// ```
+/// use aanyx::{ export_plugin, plugin_metadata, abi::RStr, plugin::PluginRegistrar};
+/// # pub trait MyPluginTrait {}
+/// # struct MyPlugin;
+/// # impl MyPluginTrait for MyPlugin {}
+///
+/// #[allow(improper_ctypes_definitions)]
+/// extern "C" fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
+///   let _ = registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
+/// }
+/// export_plugin!( register, dyn MyPluginTrait,
+///   metadata = plugin_metadata!().with_name( "My plugin" ).with_capabilities( &[RStr::new( "spell-check" ), RStr::new( "autocomplete" )] ),
+/// );
+/// ```
+///
+/// Keeping a counter across reloads:
+/// ```
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use aanyx::{ export_plugin, plugin::{PluginRegistrar, PluginState}};
+/// # pub trait MyPluginTrait {}
+/// # struct MyPlugin;
+/// # impl MyPluginTrait for MyPlugin {}
+///
+/// static COUNTER: AtomicU64 = AtomicU64::new( 0 );
+///
+/// #[allow(improper_ctypes_definitions)]
+/// extern "C" fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
+///   let _ = registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
+/// }
+/// #[allow(improper_ctypes_definitions)]
+/// extern "C" fn export_state() -> Vec<u8> {
+///   COUNTER.load( Ordering::SeqCst ).to_le_bytes().to_vec()
+/// }
+/// #[allow(improper_ctypes_definitions)]
+/// extern "C" fn import_state( state: PluginState ) -> Result<(), String> {
+///   let counter = match state.version {
+///     // The first build stored the counter as u32
+///     1 => u32::from_le_bytes( state.data.try_into().map_err( |_| "invalid state" )? ) as u64,
+///     2 => u64::from_le_bytes( state.data.try_into().map_err( |_| "invalid state" )? ),
+///     version => return Err( format!( "unknown state version {version}" ) ),
+///   };
+///   COUNTER.store( counter, Ordering::SeqCst );
+///   Ok(())
+/// }
+///
+/// export_plugin!( register, dyn MyPluginTrait, state_version = 2, export_state = export_state, import_state = import_state );
+/// ```
+///
+/// The plugin type can be any path, with generic arguments, see [`import_plugin!`](crate::import_plugin#mangling):
+/// ```
+/// use aanyx::{ export_plugin, plugin::PluginRegistrar};
+/// # pub mod api { pub mod v2 { pub trait Handler<Request> {} } }
+/// # pub struct Request;
+/// # struct MyHandler;
+/// # impl api::v2::Handler<Request> for MyHandler {}
+///
+/// #[allow(improper_ctypes_definitions)]
+/// extern "C" fn register(registrar: &mut dyn PluginRegistrar<dyn api::v2::Handler<Request>>) {
+///   let _ = registrar.register_plugin("MyHandler", Box::new(MyHandler));
+/// }
+/// export_plugin!( register, dyn api::v2::Handler<Request> );
+/// ```
+///
+/// ## Static or dynamic
+/// With `static_feature = "<feature>"` as first option, the plugin is exported with [`export_static_plugin!`](crate::export_static_plugin)
+/// when the crate is compiled with the feature, and as usual otherwise. The same crate can then be loaded from a shared library
+/// during development, and reloaded when it changes, while it's linked into the binary of the release builds.
+/// ```toml
+/// [lib]
+/// crate-type = ["cdylib", "rlib"]
+///
+/// [features]
+/// static = []
+/// ```
+/// ```
+/// use aanyx::{ export_plugin, plugin::PluginRegistrar};
+/// # pub trait MyPluginTrait {}
+/// # struct MyPlugin;
+/// # impl MyPluginTrait for MyPlugin {}
+///
+/// fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
+///   let _ = registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
+/// }
+/// export_plugin!( register, dyn MyPluginTrait, static_feature = "static", id = "my-plugin" );
+/// ```
+///
+/// The host depends on the plugin crate, with the feature, only in the release builds, and picks the manager accordingly.
+/// Both implement [`PluginManagerGet`](crate::host::PluginManagerGet), so the code using the plugins is the same:
+/// ```no_run
+/// use aanyx::import_plugin;
+/// use aanyx::host::PluginManagerGet;
+/// # trait MyPluginTrait { fn run( &self ); }
+///
+/// #[cfg(debug_assertions)]
+/// let manager = {
+///   let mut manager = aanyx::host::DylibPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+///   unsafe { aanyx::host::discover( &mut manager, "plugins", import_plugin!( dyn MyPluginTrait ) ) }.unwrap();
+///   manager
+/// };
+/// #[cfg(not(debug_assertions))]
+/// let manager = aanyx::host::StaticPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+///
+/// manager.get( &String::from("MyPlugin") ).unwrap().run();
+/// ```
 #[macro_export]
 macro_rules! export_plugin {
-  ($register:expr, dyn $plugin_type:ident ) => {
+  ($register:expr, $plugin_type:ty, static_feature = $feature:literal $(, $option:ident = $value:expr )* $(,)? ) => {
+    #[cfg(not(feature = $feature))]
+    $crate::export_plugin!( $register, $plugin_type $(, $option = $value )* );
+    #[cfg(feature = $feature)]
+    $crate::export_static_plugin!( $register, $plugin_type $(, $option = $value )* );
+  };
+  ($register:expr, $plugin_type:ty $(, $option:ident = $value:expr )* $(,)? ) => {
     $crate::plugin::plugin_paste::paste! {
-    #[doc(hidden)]
-    #[no_mangle]
-    pub static [<plugin_declaration _ dyn _  $plugin_type >]: $crate::plugin::PluginDeclaration::<dyn $plugin_type> = $crate::plugin::PluginDeclaration {
-      rustc_version: $crate::RUSTC_VERSION,
-      nyx_version: $crate::CORE_VERSION,
-      register: $register,
-    };
+    $crate::plugin::__export_declaration!( $crate; $plugin_type;
+      $crate::plugin::PluginDeclaration::new( {
+          #[allow(improper_ctypes_definitions)]
+          extern "C" fn __aanyx_register( registrar: &mut dyn $crate::plugin::PluginRegistrar<$plugin_type> ) {
+            ( $register )( registrar )
+          }
+          __aanyx_register
+        }, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") )
+        .with_try_register( {
+          #[allow(improper_ctypes_definitions)]
+          extern "C" fn __aanyx_try_register( registrar: &mut dyn $crate::plugin::PluginRegistrar<$plugin_type> ) -> Result<(), String> {
+            $crate::plugin::catch_panic( || ( $register )( registrar ) )
+          }
+          __aanyx_try_register
+        })
+        .with_metadata( $crate::plugin_metadata!() ) $( .[<with_ $option>]( $value ) )* );
     }
   };
-  ($register:expr, $plugin_type: ident) => {
+}
+
+/// Like [`export_plugin!`], but for plugins linked into the binary of the host instead of being loaded from a shared library.
+/// The declaration is collected at link time and the [`StaticPluginManager`](crate::host::StaticPluginManager) registers it
+/// as the [`DylibPluginManager`](crate::host::DylibPluginManager) does, so the host uses the plugins in the same way.
+/// It accepts the same options of [`export_plugin!`].
+///
+/// Many crates can export the same plugin type, because the declaration is not exported with a symbol.
+/// The crate must be used by the binary, even with just `extern crate my_plugin;`, otherwise it's not linked.
+/// ```
+/// use aanyx::{ export_static_plugin, import_plugin, plugin::PluginRegistrar};
+/// use aanyx::host::{PluginManagerGet, StaticPluginManager};
+/// pub trait MyPluginTrait { fn run( &self ) -> u32; }
+/// struct MyPlugin;
+/// impl MyPluginTrait for MyPlugin { fn run( &self ) -> u32 { 42 } }
+///
+/// fn register(registrar: &mut dyn PluginRegistrar<dyn MyPluginTrait>) {
+///   let _ = registrar.register_plugin("MyPlugin", Box::new(MyPlugin));
+/// }
+/// export_static_plugin!( register, dyn MyPluginTrait, id = "my-plugin" );
+///
+/// let manager = StaticPluginManager::<dyn MyPluginTrait>::new( import_plugin!( dyn MyPluginTrait ) );
+/// assert_eq!( manager.get( &String::from("MyPlugin") ).unwrap().run(), 42 );
+/// ```
+#[macro_export]
+macro_rules! export_static_plugin {
+  ($register:expr, $plugin_type:ty $(, $option:ident = $value:expr )* $(,)? ) => {
     $crate::plugin::plugin_paste::paste! {
-    #[doc(hidden)]
-    #[no_mangle]
-    pub static [<plugin_declaration _  $plugin_type >]: $crate::plugin::PluginDeclaration::<$plugin_type> = $crate::plugin::PluginDeclaration {
-      rustc_version: $crate::RUSTC_VERSION,
-      nyx_version: $crate::CORE_VERSION,
-      register: $register,
-    };
+    $crate::plugin::__export_static_declaration!( $crate; $plugin_type;
+      $crate::plugin::PluginDeclaration::new( {
+          #[allow(improper_ctypes_definitions)]
+          extern "C" fn __aanyx_register( registrar: &mut dyn $crate::plugin::PluginRegistrar<$plugin_type> ) {
+            ( $register )( registrar )
+          }
+          __aanyx_register
+        }, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") )
+        .with_try_register( {
+          #[allow(improper_ctypes_definitions)]
+          extern "C" fn __aanyx_try_register( registrar: &mut dyn $crate::plugin::PluginRegistrar<$plugin_type> ) -> Result<(), String> {
+            $crate::plugin::catch_panic( || ( $register )( registrar ) )
+          }
+          __aanyx_try_register
+        })
+        .with_metadata( $crate::plugin_metadata!() ) $( .[<with_ $option>]( $value ) )* );
     }
   };
 }
 
+/// The [`MetadataDeclaration`] of the crate being compiled, read from its `Cargo.toml`: the name, version, authors,
+/// description and license of the package. It's the default `metadata` of [`export_plugin!`] and can be customized with the
+/// `with_` methods.
+/// ```
+/// use aanyx::abi::RStr;
+/// use aanyx::plugin_metadata;
+///
+/// const METADATA: aanyx::plugin::MetadataDeclaration = plugin_metadata!().with_capabilities( &[RStr::new( "greet" )] );
+/// assert_eq!( METADATA.name.as_str(), "aanyx" );
+/// assert_eq!( METADATA.version.as_str(), env!("CARGO_PKG_VERSION") );
+/// ```
+#[macro_export]
+macro_rules! plugin_metadata {
+  () => {
+    $crate::plugin::MetadataDeclaration::new( env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION") )
+      .with_authors( env!("CARGO_PKG_AUTHORS") )
+      .with_description( env!("CARGO_PKG_DESCRIPTION") )
+      .with_license( env!("CARGO_PKG_LICENSE") )
+  };
+}
+
 /// This is a macro that generates the name of the plugin declaration in a module.
 /// Using this makes easier to check type compatibility between plugins and host systems, 
 /// because if the plugin doesn't support the requested plugin type then no declaration will be found.
@@ -89,36 +861,43 @@ macro_rules! export_plugin {
 /// assert_eq!( import_plugin!( dyn MyPluginTrait ), b"plugin_declaration_dyn_MyPluginTrait");
 /// ```
 /// 
-/// ## Limitations
-/// Because of Rust macros limitations a type cannot contains `::`. 
-/// This means that this is invalid: 
-/// ```compile_fail, no_run
+/// Paths and generic arguments are supported too:
+/// ```
 /// use aanyx::import_plugin;
-/// import_plugin!( myplugin::MyPlugin )
+/// assert_eq!( import_plugin!( dyn api::v2::Plugin ), b"plugin_declaration_dyn_api_3a_3av2_3a_3aPlugin");
+/// assert_eq!( import_plugin!( dyn Handler<Request> ), b"plugin_declaration_dyn_Handler_3cRequest_3e");
 /// ```
-/// A workaround is the following:
+///
+/// ## Mangling
+/// The name is computed from the tokens of the type, so the host and the plugin must write the type in the same way.
+/// Spaces and a leading `::` don't matter, but the path does: `dyn api::Plugin` and `dyn Plugin` are different names even
+/// if `api::Plugin` has been imported. The same happens with type aliases. Using the full path of the type in the crate
+/// that defines it is the safest choice.
 /// ```
-/// # mod myplugin { pub struct MyPlugin {} }
-/// use myplugin::MyPlugin as MyPlugin;
 /// use aanyx::import_plugin;
-/// 
-/// import_plugin!( MyPlugin );
+/// # mod api { pub trait Plugin {} }
+/// use api::Plugin;
+/// type Alias = dyn Plugin;
+///
+/// assert_eq!( import_plugin!( dyn ::api :: Plugin ), import_plugin!( dyn api::Plugin ) );
+/// assert_ne!( import_plugin!( dyn api::Plugin ), import_plugin!( dyn Plugin ) );
+/// assert_ne!( import_plugin!( Alias ), import_plugin!( dyn Plugin ) );
 /// ```
-/// Also using a type alias makes a result not compatible between host and plugin.
+///
+/// The name is `plugin_declaration_`, followed by `dyn_` for trait objects, followed by the rest of the type, unchanged if
+/// it's a single identifier or else escaped, replacing every byte of its UTF-8 encoding that is not an ASCII letter or digit with `_` and its two hex digits.
+/// The identifiers that could be read as escaped, because they contain `_` followed by two hex digits or start with `dyn_`
+/// without being trait objects, are escaped too, so different types always have different names:
 /// ```
-/// # mod myplugin { pub struct MyPlugin {} }
 /// use aanyx::import_plugin;
-/// use myplugin::MyPlugin as MyPlugin;
-/// type MyAlias = MyPlugin;
-/// 
-/// assert_ne!( import_plugin!( MyAlias ), import_plugin!( MyPlugin ) );
+/// assert_eq!( import_plugin!( dyn a_3a_3aB ), b"plugin_declaration_dyn_a_5f3a_5f3aB" );
+/// assert_ne!( import_plugin!( dyn a_3a_3aB ), import_plugin!( dyn a::B ) );
+/// assert_eq!( import_plugin!( dyn_Plugin ), b"plugin_declaration__64yn_5fPlugin" );
+/// assert_eq!( import_plugin!( my_plugin ), b"plugin_declaration_my_plugin" );
 /// ```
 #[macro_export]
 macro_rules! import_plugin {
-  ( dyn $plugin_type:ident ) => {
-     ( concat!("plugin_declaration_dyn_", stringify!( $plugin_type )) ).as_bytes()
+  ( $plugin_type:ty ) => {
+    $crate::plugin::__plugin_symbol!( $plugin_type )
   };
-  ( $plugin_type:ident ) => {
-    ( concat!("plugin_declaration_", stringify!( $plugin_type )) ).as_bytes()
-  };
-}
\ No newline at end of file
+}
@@ -1,7 +1,24 @@
 //! A `Registry` is to be intended as a big container from which we can extract data based on their type. 
-//! A simple example is the `HashMap<TypeId, Box<Any>>`
+//! A simple example is the `HashMap<TypeId, Box<Any>>`, provided as [`TypeRegistry`] with the [`Res`] and [`ResMut`] arguments.
 //! A [`System`](crate::system::System) is any function that accepts any number (up to 15 because of implementation details) 
 //! of arguments supporting the `FromRegistry` trait.
+//! When the registry may not contain what a system needs, the arguments implement [`TryFromRegistry`] and the system is
+//! called with [`System::try_apply`].
+//! To write into the registry the system borrows it mutably with [`System::apply_mut`], see [`FromRegistryMut`]. The [`TypeRegistry`]
+//! borrows its resources at runtime, so it supports both [`System::apply`] and [`System::apply_mut`].
+//! To store systems with different arguments together, convert them into a [`BoxedSystem`] with [`IntoSystem`].
+
+use std::any::type_name;
+use std::error::Error;
+use std::fmt;
+
+/// A ready-made registry with one value for each type.
+pub mod registry;
+pub use registry::{Res, ResMut, ResourceError, TypeRegistry};
+
+/// Systems with erased arguments, to be stored in collections.
+pub mod boxed;
+pub use boxed::{BoxedSystem, IntoSystem, SystemArg};
 
 /// Defines how a custom datatype can be extracted from a registry to be passed into a system. 
 /// ```
@@ -11,7 +28,7 @@
 /// 
 /// impl MyDataType { pub fn return_true( &self ) -> bool { true } }
 /// 
-/// impl FromRegistry< MyRegistry > for MyDataType {
+/// impl FromRegistry<'_, MyRegistry > for MyDataType {
 ///   fn from_registry( registry: &MyRegistry ) -> Self  {
 ///     // Logic to extract MyDatatype
 ///     MyDataType {}
@@ -26,9 +43,9 @@
 /// # struct MyRegistry {}
 /// # let my_registry = MyRegistry {};
 /// # trait MyDefault:Default {}
-/// # impl FromRegistry< MyRegistry > for String {  fn from_registry( _registry: &MyRegistry ) -> Self { Self::default() } } 
-/// # impl FromRegistry< MyRegistry > for u8 {  fn from_registry( _registry: &MyRegistry ) -> Self { Self::default() } } 
-/// # impl FromRegistry< MyRegistry > for usize {  fn from_registry( _registry: &MyRegistry ) -> Self { Self::default() } } 
+/// # impl FromRegistry<'_, MyRegistry > for String {  fn from_registry( _registry: &MyRegistry ) -> Self { Self::default() } } 
+/// # impl FromRegistry<'_, MyRegistry > for u8 {  fn from_registry( _registry: &MyRegistry ) -> Self { Self::default() } } 
+/// # impl FromRegistry<'_, MyRegistry > for usize {  fn from_registry( _registry: &MyRegistry ) -> Self { Self::default() } } 
 /// 
 /// // These are equivalents
 /// let (a0, b0, c0 /*, others... */) = <(String, u8, usize /*, Others... */)>::from_registry( &my_registry );
@@ -39,29 +56,26 @@
 /// assert_eq!( b0, b1 );
 /// assert_eq!( c0, c1 );
 /// ```
-pub trait FromRegistry<Registry> {
-  // TODO: make the function argument be mutable?
-  fn from_registry( registry: &Registry ) -> Self;
+pub trait FromRegistry<'r, Registry> {
+  fn from_registry( registry: &'r Registry ) -> Self;
 }
 
 macro_rules! impl_from_registry {
   ( $( $x:ident ),* ) => {
-    impl<Registry, $( $x: FromRegistry<Registry>),* > FromRegistry<Registry> for ( $( $x ),* ) {
-      fn from_registry( registry: &Registry ) -> Self {
+    impl<'r, Registry, $( $x: FromRegistry<'r, Registry>),* > FromRegistry<'r, Registry> for ( $( $x ),* ) {
+      fn from_registry( registry: &'r Registry ) -> Self {
           ( $( $x::from_registry(registry) ),* )
       }
     }
   };
 }
 
-impl<Registry> FromRegistry<Registry> for () {
-  fn from_registry( _: &Registry ) -> Self {
-    ()
-  }
+impl<Registry> FromRegistry<'_, Registry> for () {
+  fn from_registry( _: &Registry ) -> Self {}
 }
 
-impl<A: FromRegistry<Registry>, Registry> FromRegistry<Registry> for (A, ) {
-    fn from_registry( registry: &Registry ) -> Self {
+impl<'r, A: FromRegistry<'r, Registry>, Registry> FromRegistry<'r, Registry> for (A, ) {
+    fn from_registry( registry: &'r Registry ) -> Self {
         ( A::from_registry( registry ), )
     }
 }
@@ -80,30 +94,239 @@ impl_from_registry!( A, B, C, D, E, F, G, H, I, J, K, L, M );
 impl_from_registry!( A, B, C, D, E, F, G, H, I, J, K, L, M, N );
 impl_from_registry!( A, B, C, D, E, F, G, H, I, J, K, L, M, N, O );
 
+/// A registry that can be split into parts borrowed separately, so that a system can borrow mutably more than one of them.
+///
+/// The parts are usually a struct with an `Option<&'r mut T>` for each resource: the first argument taking a resource
+/// leaves `None` to the others, so the same resource can't be borrowed twice.
+pub trait SplitRegistry<'r> {
+  type Parts;
+  fn split( &'r mut self ) -> Self::Parts;
+}
+
+/// Defines how a custom datatype borrows mutably a part of a registry to be passed into a system.
+/// ```
+/// use aanyx::system::{FromRegistryMut, SplitRegistry, System};
+///
+/// struct Counters { hits: u32, misses: u32 }
+/// struct CountersParts<'r> { hits: Option<&'r mut u32>, misses: Option<&'r mut u32> }
+///
+/// impl<'r> SplitRegistry<'r> for Counters {
+///   type Parts = CountersParts<'r>;
+///   fn split( &'r mut self ) -> Self::Parts {
+///     CountersParts { hits: Some( &mut self.hits ), misses: Some( &mut self.misses ) }
+///   }
+/// }
+///
+/// struct Hits<'r>( &'r mut u32 );
+/// struct Misses<'r>( &'r mut u32 );
+///
+/// impl<'r> FromRegistryMut<'r, Counters> for Hits<'r> {
+///   fn from_registry_mut( parts: &mut CountersParts<'r> ) -> Self {
+///     Hits( parts.hits.take().expect( "the hits are already borrowed" ) )
+///   }
+/// }
+/// # impl<'r> FromRegistryMut<'r, Counters> for Misses<'r> {
+/// #   fn from_registry_mut( parts: &mut CountersParts<'r> ) -> Self {
+/// #     Misses( parts.misses.take().expect( "the misses are already borrowed" ) )
+/// #   }
+/// # }
+///
+/// fn lookup( hits: Hits, misses: Misses ) { *hits.0 += 1; *misses.0 += 2; }
+///
+/// let mut counters = Counters { hits: 0, misses: 0 };
+/// lookup.apply_mut( &mut counters );
+/// lookup.apply_mut( &mut counters );
+/// assert_eq!( (counters.hits, counters.misses), (2, 4) );
+/// ```
+/// It is automatically implemented for tuples up to 15 parameters, extracted in order from the same parts. Two arguments
+/// borrowing the same resource are reported by the parts at runtime, in the example above with a panic.
+/// ```should_panic
+/// # use aanyx::system::{FromRegistryMut, SplitRegistry, System};
+/// # struct Counters { hits: u32 }
+/// # struct CountersParts<'r> { hits: Option<&'r mut u32> }
+/// # impl<'r> SplitRegistry<'r> for Counters {
+/// #   type Parts = CountersParts<'r>;
+/// #   fn split( &'r mut self ) -> Self::Parts { CountersParts { hits: Some( &mut self.hits ) } }
+/// # }
+/// # struct Hits<'r>( &'r mut u32 );
+/// # impl<'r> FromRegistryMut<'r, Counters> for Hits<'r> {
+/// #   fn from_registry_mut( parts: &mut CountersParts<'r> ) -> Self {
+/// #     Hits( parts.hits.take().expect( "the hits are already borrowed" ) )
+/// #   }
+/// # }
+/// fn aliasing( _: Hits, _: Hits ) {}
+///
+/// aliasing.apply_mut( &mut Counters { hits: 0 } );
+/// ```
+pub trait FromRegistryMut<'r, Registry: SplitRegistry<'r>>: Sized {
+  fn from_registry_mut( parts: &mut Registry::Parts ) -> Self;
+}
+
+macro_rules! impl_from_registry_mut {
+  ( $( $x:ident ),* ) => {
+    impl<'r, Registry: SplitRegistry<'r>, $( $x: FromRegistryMut<'r, Registry> ),* > FromRegistryMut<'r, Registry> for ( $( $x, )* ) {
+      fn from_registry_mut( parts: &mut Registry::Parts ) -> Self {
+        ( $( $x::from_registry_mut( parts ), )* )
+      }
+    }
+  };
+}
+
+impl<'r, Registry: SplitRegistry<'r>> FromRegistryMut<'r, Registry> for () {
+  fn from_registry_mut( _: &mut Registry::Parts ) -> Self {}
+}
+
+impl_from_registry_mut!( A );
+impl_from_registry_mut!( A, B );
+impl_from_registry_mut!( A, B, C );
+impl_from_registry_mut!( A, B, C, D );
+impl_from_registry_mut!( A, B, C, D, E );
+impl_from_registry_mut!( A, B, C, D, E, F );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H, I );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H, I, J );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H, I, J, K );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H, I, J, K, L );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H, I, J, K, L, M );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H, I, J, K, L, M, N );
+impl_from_registry_mut!( A, B, C, D, E, F, G, H, I, J, K, L, M, N, O );
+
+/// Defines how a custom datatype can be extracted from a registry that may not contain it.
+/// ```
+/// use aanyx::system::TryFromRegistry;
+/// # use std::collections::HashMap;
+/// struct Name( String );
+///
+/// impl TryFromRegistry<'_, HashMap<&str, String> > for Name {
+///   type Error = String;
+///   fn try_from_registry( registry: &HashMap<&str, String> ) -> Result<Self, Self::Error> {
+///     registry.get( "name" ).map( |name| Name( name.clone() ) ).ok_or( String::from("the name is missing") )
+///   }
+/// }
+///
+/// let registry = HashMap::from( [("name", String::from("Alice"))] );
+/// assert_eq!( Name::try_from_registry( &registry ).unwrap().0, "Alice" );
+/// assert!( Name::try_from_registry( &HashMap::new() ).is_err() );
+/// ```
+/// It is automatically implemented for tuples up to 15 parameters, whose error is an [`ExtractError`] reporting the first
+/// element that could not be extracted.
+/// ```
+/// # use aanyx::system::TryFromRegistry;
+/// # use std::collections::HashMap;
+/// # struct Name( String );
+/// # impl TryFromRegistry<'_, HashMap<&str, String> > for Name {
+/// #   type Error = String;
+/// #   fn try_from_registry( registry: &HashMap<&str, String> ) -> Result<Self, Self::Error> {
+/// #     registry.get( "name" ).map( |name| Name( name.clone() ) ).ok_or( String::from("the name is missing") )
+/// #   }
+/// # }
+/// let error = <((), Name)>::try_from_registry( &HashMap::new() ).err().unwrap();
+/// assert_eq!( error.index, 1 );
+/// assert!( error.type_name.ends_with( "Name" ) );
+/// assert_eq!( error.reason, "the name is missing" );
+/// ```
+pub trait TryFromRegistry<'r, Registry>: Sized {
+  /// Why the value could not be extracted
+  type Error;
+  fn try_from_registry( registry: &'r Registry ) -> Result<Self, Self::Error>;
+}
+
+/// The argument of a system that could not be extracted from the registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractError {
+  /// The position of the argument, starting from 0
+  pub index: usize,
+  /// The name of the type of the argument, as returned by [`std::any::type_name`]
+  pub type_name: &'static str,
+  /// The error returned by [`TryFromRegistry::try_from_registry`]
+  pub reason: String,
+}
+
+impl ExtractError {
+  fn new<T>( index: usize, reason: impl fmt::Display ) -> Self {
+    Self { index, type_name: type_name::<T>(), reason: reason.to_string() }
+  }
+}
+
+impl fmt::Display for ExtractError {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    write!( f, "cannot extract the argument {} of type {}: {}", self.index, self.type_name, self.reason )
+  }
+}
+
+impl Error for ExtractError {}
+
+macro_rules! impl_try_from_registry {
+  ( $( $index:literal $x:ident ),* ) => {
+    impl<'r, Registry, $( $x: TryFromRegistry<'r, Registry> ),* > TryFromRegistry<'r, Registry> for ( $( $x, )* ) where $( $x::Error: fmt::Display ),* {
+      type Error = ExtractError;
+      fn try_from_registry( registry: &'r Registry ) -> Result<Self, Self::Error> {
+        Ok(( $( $x::try_from_registry( registry ).map_err( |error| ExtractError::new::<$x>( $index, error ) )?, )* ))
+      }
+    }
+  };
+}
+
+impl<Registry> TryFromRegistry<'_, Registry> for () {
+  type Error = ExtractError;
+  fn try_from_registry( _: &Registry ) -> Result<Self, Self::Error> {
+    Ok(())
+  }
+}
+
+impl_try_from_registry!( 0 A );
+impl_try_from_registry!( 0 A, 1 B );
+impl_try_from_registry!( 0 A, 1 B, 2 C );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N );
+impl_try_from_registry!( 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O );
+
 
 /// A system is a trait that allows a function to be called giving only a registry and letting the compiler
 /// figure out what needs to be extracted from that registry in order to call the function
 /// ```
 /// use aanyx::system::System;
-/// use std::rc::Rc;
 /// 
-/// struct Data<T>{ data: Rc<T> }
+/// struct Data<'r, T>{ data: &'r T }
 /// 
 /// # use aanyx::system::FromRegistry;
-/// # impl FromRegistry<(Rc<Person>, Location)> for Data<Person> { fn from_registry( (person, _) :&(Rc<Person>, Location)) -> Self { Data { data: Rc::clone(person) } }}
+/// # impl<'r> FromRegistry<'r, (Person, Location)> for Data<'r, Person> { fn from_registry( (person, _) :&'r (Person, Location)) -> Self { Data { data: person } }}
 /// // Simulate other data
 /// struct Location {}
 /// struct Person{ name: String, age: u8 }
 /// fn old_enough( person: Data<Person> ) -> bool { person.data.age > 30 }
 /// 
-/// let registry0 = ( Rc::new(Person{ name: String::from("Alice"), age: 24u8 }),  Location { /* Location data */ }) ;
-/// let registry1 = ( Rc::new(Person{ name: String::from("Bob"), age: 31u8 }),    Location { /* Location data */ });
-/// let registry2 = ( Rc::new(Person{ name: String::from("Charlie"), age: 1u8 }), Location { /* Location data */ });
+/// let registry0 = ( Person{ name: String::from("Alice"), age: 24u8 },  Location { /* Location data */ }) ;
+/// let registry1 = ( Person{ name: String::from("Bob"), age: 31u8 },    Location { /* Location data */ });
+/// let registry2 = ( Person{ name: String::from("Charlie"), age: 1u8 }, Location { /* Location data */ });
 /// 
 /// assert_eq!( old_enough.apply( &registry0 ), false );
 /// assert_eq!( old_enough.apply( &registry1 ), true );
 /// assert_eq!( old_enough.apply( &registry2 ), false );
 /// ```
+/// The arguments borrow the registry for the duration of the call, so they can also be plain references.
+/// ```
+/// use aanyx::system::{FromRegistry, System};
+/// # struct Location {}
+/// # struct Person{ name: String, age: u8 }
+///
+/// impl<'r> FromRegistry<'r, (Person, Location)> for &'r Person {
+///   fn from_registry( (person, _): &'r (Person, Location) ) -> Self { person }
+/// }
+///
+/// fn name( person: &Person ) -> &str { &person.name }
+///
+/// let registry = ( Person{ name: String::from("Alice"), age: 24u8 }, Location {} );
+/// assert_eq!( name.apply( &registry ), "Alice" );
+/// ```
 /// ## Closures
 /// A closure can be made into a `System` only if the closure accepts no arguments
 /// ```
@@ -127,6 +350,37 @@ impl_from_registry!( A, B, C, D, E, F, G, H, I, J, K, L, M, N, O );
 /// 
 /// assert!( always_true_wrapper.apply( &registry ))
 /// ```
+/// ## Fallible extraction
+/// When the arguments implement [`TryFromRegistry`], [`System::try_apply`] calls the function only if all of them can be
+/// extracted, otherwise it returns an [`ExtractError`] naming the first missing argument.
+/// ```
+/// use aanyx::system::{System, TryFromRegistry};
+/// # use std::collections::HashMap;
+/// struct Name( String );
+/// struct Surname( String );
+/// # impl TryFromRegistry<'_, HashMap<&str, String> > for Name {
+/// #   type Error = &'static str;
+/// #   fn try_from_registry( registry: &HashMap<&str, String> ) -> Result<Self, Self::Error> {
+/// #     registry.get( "name" ).map( |name| Name( name.clone() ) ).ok_or( "missing" )
+/// #   }
+/// # }
+/// # impl TryFromRegistry<'_, HashMap<&str, String> > for Surname {
+/// #   type Error = &'static str;
+/// #   fn try_from_registry( registry: &HashMap<&str, String> ) -> Result<Self, Self::Error> {
+/// #     registry.get( "surname" ).map( |surname| Surname( surname.clone() ) ).ok_or( "missing" )
+/// #   }
+/// # }
+///
+/// fn full_name( name: Name, surname: Surname ) -> String { format!( "{} {}", name.0, surname.0 ) }
+///
+/// let registry = HashMap::from( [("name", String::from("Alice"))] );
+/// let error = full_name.try_apply( &registry ).unwrap_err();
+/// assert_eq!( error.to_string(), format!( "cannot extract the argument 1 of type {}: missing", std::any::type_name::<Surname>() ) );
+///
+/// let registry = HashMap::from( [("name", String::from("Alice")), ("surname", String::from("Smith"))] );
+/// assert_eq!( full_name.try_apply( &registry ).unwrap(), "Alice Smith" );
+/// ```
+///
 /// ## Performance analysis
 /// Benchamrks performed suggests that the overhead of calling a system insted of the original function is negligible.
 /// 
@@ -135,37 +389,66 @@ impl_from_registry!( A, B, C, D, E, F, G, H, I, J, K, L, M, N, O );
 /// 
 /// ## Safety
 /// Calling a unsafe function through the `System` trait will not generate any new unsafeties, but the function will still be unsafe.
-pub trait System<Registry, Args: FromRegistry<Registry>>{
+pub trait System<Registry, Args>{
   type Return;
-  fn apply( &self, registry: &Registry ) -> Self::Return;
+  fn apply<'r>( &self, registry: &'r Registry ) -> Self::Return where Args: FromRegistry<'r, Registry>;
+  /// Call the function only if all the arguments can be extracted from the registry
+  fn try_apply<'r>( &self, registry: &'r Registry ) -> Result<Self::Return, <Args as TryFromRegistry<'r, Registry>>::Error> where Args: TryFromRegistry<'r, Registry>;
+  /// Call the function borrowing the registry mutably, so that the arguments can write into it
+  fn apply_mut<'r>( &self, registry: &'r mut Registry ) -> Self::Return where Registry: SplitRegistry<'r>, Args: FromRegistryMut<'r, Registry>;
 }
 
 macro_rules! impl_system_for {
   ( $( $x:ident ),* ) => {
-    impl<Registry, Func, FnReturnType, $( $x:FromRegistry<Registry> ),*> System<Registry, ($($x),*)> for Func where Func: Fn($($x),*) -> FnReturnType {
+    impl<Registry, Func, FnReturnType, $( $x ),*> System<Registry, ($($x),*)> for Func where Func: Fn($($x),*) -> FnReturnType {
       type Return = FnReturnType;
       #[allow(non_snake_case)]
-      fn apply( &self, registry: &Registry ) -> Self::Return {
-        let ($($x),*) =  ($($x::from_registry( registry )),*);
+      fn apply<'r>( &self, registry: &'r Registry ) -> Self::Return where ($($x),*): FromRegistry<'r, Registry> {
+        let ($($x),*) = <($($x),*)>::from_registry( registry );
+        (self)($($x),*)
+      }
+      #[allow(non_snake_case)]
+      fn try_apply<'r>( &self, registry: &'r Registry ) -> Result<Self::Return, <($($x),*) as TryFromRegistry<'r, Registry>>::Error> where ($($x),*): TryFromRegistry<'r, Registry> {
+        let ($($x),*) = <($($x),*)>::try_from_registry( registry )?;
+        Ok( (self)($($x),*) )
+      }
+      #[allow(non_snake_case)]
+      fn apply_mut<'r>( &self, registry: &'r mut Registry ) -> Self::Return where Registry: SplitRegistry<'r>, ($($x),*): FromRegistryMut<'r, Registry> {
+        let ($($x),*) = <($($x),*)>::from_registry_mut( &mut registry.split() );
         (self)($($x),*)
       }
     }
   };
 }
 
-impl<Registry, F, A: FromRegistry<Registry>, FnReturnType> System<Registry, (A, )> for F where F: Fn(A) -> FnReturnType {
+impl<Registry, F, A, FnReturnType> System<Registry, (A, )> for F where F: Fn(A) -> FnReturnType {
   type Return = FnReturnType;
-  fn apply( &self, registry: &Registry) -> Self::Return {
-    let a = A::from_registry( registry );
+  fn apply<'r>( &self, registry: &'r Registry) -> Self::Return where (A, ): FromRegistry<'r, Registry> {
+    let (a, ) = <(A, )>::from_registry( registry );
+    (self)(a)
+  }
+  fn try_apply<'r>( &self, registry: &'r Registry ) -> Result<Self::Return, <(A, ) as TryFromRegistry<'r, Registry>>::Error> where (A, ): TryFromRegistry<'r, Registry> {
+    let (a, ) = <(A, )>::try_from_registry( registry )?;
+    Ok( (self)(a) )
+  }
+  fn apply_mut<'r>( &self, registry: &'r mut Registry ) -> Self::Return where Registry: SplitRegistry<'r>, (A, ): FromRegistryMut<'r, Registry> {
+    let (a, ) = <(A, )>::from_registry_mut( &mut registry.split() );
     (self)(a)
   }
 }
 
 impl<Registry, F, FnReturnType> System<Registry, ()> for F where F: Fn() -> FnReturnType {
   type Return = FnReturnType;
-  fn apply( &self, _: &Registry) -> Self::Return {
+  fn apply<'r>( &self, _: &'r Registry) -> Self::Return where (): FromRegistry<'r, Registry> {
       (self)()
   }
+  fn try_apply<'r>( &self, registry: &'r Registry ) -> Result<Self::Return, <() as TryFromRegistry<'r, Registry>>::Error> where (): TryFromRegistry<'r, Registry> {
+    <()>::try_from_registry( registry )?;
+    Ok( (self)() )
+  }
+  fn apply_mut<'r>( &self, _: &'r mut Registry ) -> Self::Return where Registry: SplitRegistry<'r> {
+    (self)()
+  }
 }
 
 
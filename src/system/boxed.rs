@@ -0,0 +1,125 @@
+//! Systems whose arguments are erased, so that systems with different arguments can be stored together.
+//! ```
+//! use aanyx::system::{BoxedSystem, IntoSystem, Res, ResMut, TypeRegistry};
+//!
+//! struct Log( Vec<String> );
+//! struct Name( &'static str );
+//!
+//! fn hello( mut log: ResMut<Log> ) { log.0.push( String::from("hello") ); }
+//! fn greet( name: Res<Name>, mut log: ResMut<Log> ) { log.0.push( format!( "greet {}", name.0 ) ); }
+//!
+//! let systems: Vec<BoxedSystem<TypeRegistry, ()>> = vec![ hello.into_system(), greet.into_system() ];
+//!
+//! let mut registry = TypeRegistry::new();
+//! registry.insert( Log( Vec::new() ) );
+//! registry.insert( Name( "Ada" ) );
+//! for system in &systems {
+//!   system.run( &registry );
+//! }
+//! assert_eq!( registry.get::<Log>().unwrap().0, ["hello", "greet Ada"] );
+//! ```
+
+use std::fmt;
+
+use crate::system::FromRegistry;
+
+/// An argument of a system converted with [`IntoSystem`], which must be extracted from every borrow of the registry.
+///
+/// The arguments borrowing the registry name the same type with a different lifetime, the others are always the same type.
+/// ```
+/// use aanyx::system::{BoxedSystem, FromRegistry, IntoSystem, SystemArg};
+///
+/// struct Answer( u32 );
+///
+/// impl FromRegistry<'_, u32> for Answer {
+///   fn from_registry( registry: &u32 ) -> Self { Answer( *registry ) }
+/// }
+///
+/// impl SystemArg<u32> for Answer {
+///   type Item<'r> = Self;
+/// }
+///
+/// fn double( answer: Answer ) -> u32 { answer.0 * 2 }
+///
+/// let system: BoxedSystem<u32, u32> = double.into_system();
+/// assert_eq!( system.run( &21 ), 42 );
+/// ```
+pub trait SystemArg<Registry> {
+  /// The argument extracted from a registry borrowed for `'r`
+  type Item<'r>: FromRegistry<'r, Registry>;
+}
+
+impl<T: ?Sized + 'static, Registry> SystemArg<Registry> for &T where for<'r> &'r T: FromRegistry<'r, Registry> {
+  type Item<'r> = &'r T;
+}
+
+/// A system of `Registry` returning `Out`, whatever its arguments are
+pub struct BoxedSystem<Registry, Out> {
+  system: Box<dyn Fn( &Registry ) -> Out>,
+}
+
+impl<Registry, Out> BoxedSystem<Registry, Out> {
+  /// Box a function taking the whole registry
+  pub fn new( system: impl Fn( &Registry ) -> Out + 'static ) -> Self {
+    Self { system: Box::new( system ) }
+  }
+
+  /// Extract the arguments from the registry and call the system
+  pub fn run( &self, registry: &Registry ) -> Out {
+    (self.system)( registry )
+  }
+}
+
+impl<Registry, Out> fmt::Debug for BoxedSystem<Registry, Out> {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    f.debug_struct( "BoxedSystem" ).finish_non_exhaustive()
+  }
+}
+
+/// Converts a function into a [`BoxedSystem`].
+///
+/// It is implemented for the functions up to 15 arguments implementing [`SystemArg`].
+pub trait IntoSystem<Registry, Args> {
+  type Out;
+  fn into_system( self ) -> BoxedSystem<Registry, Self::Out>;
+}
+
+macro_rules! impl_into_system {
+  ( $( $x:ident ),* ) => {
+    impl<Registry, Func, FnReturnType, $( $x: SystemArg<Registry> ),*> IntoSystem<Registry, ($($x,)*)> for Func
+    where Func: Fn($($x),*) -> FnReturnType + for<'r> Fn($($x::Item<'r>),*) -> FnReturnType + 'static {
+      type Out = FnReturnType;
+      #[allow(non_snake_case)]
+      fn into_system( self ) -> BoxedSystem<Registry, FnReturnType> {
+        // Call through the higher-ranked bound, with the arguments borrowing the registry only during the call
+        fn call<Registry, FnReturnType, $( $x: SystemArg<Registry> ),*>( function: &impl for<'r> Fn($($x::Item<'r>),*) -> FnReturnType, registry: &Registry ) -> FnReturnType {
+          let ($($x,)*) = <($($x::Item<'_>,)*)>::from_registry( registry );
+          function($($x),*)
+        }
+        BoxedSystem::new( move |registry| call::<Registry, FnReturnType, $($x),*>( &self, registry ) )
+      }
+    }
+  };
+}
+
+impl<Registry, Func, FnReturnType> IntoSystem<Registry, ()> for Func where Func: Fn() -> FnReturnType + 'static {
+  type Out = FnReturnType;
+  fn into_system( self ) -> BoxedSystem<Registry, FnReturnType> {
+    BoxedSystem::new( move |_| (self)() )
+  }
+}
+
+impl_into_system!( A );
+impl_into_system!( A, B );
+impl_into_system!( A, B, C );
+impl_into_system!( A, B, C, D );
+impl_into_system!( A, B, C, D, E );
+impl_into_system!( A, B, C, D, E, F );
+impl_into_system!( A, B, C, D, E, F, G, H );
+impl_into_system!( A, B, C, D, E, F, G, H, I );
+impl_into_system!( A, B, C, D, E, F, G, H, I, J );
+impl_into_system!( A, B, C, D, E, F, G, H, I, J, K );
+impl_into_system!( A, B, C, D, E, F, G, H, I, J, K, L );
+impl_into_system!( A, B, C, D, E, F, G, H, I, J, K, L, M );
+impl_into_system!( A, B, C, D, E, F, G, H, I, J, K, L, M, N );
+impl_into_system!( A, B, C, D, E, F, G, H, I, J, K, L, M, N, O );
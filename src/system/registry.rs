@@ -0,0 +1,277 @@
+//! A registry holding one value for each type, from which the systems extract [`Res`] and [`ResMut`].
+//! ```
+//! use aanyx::system::{Res, ResMut, System, TypeRegistry};
+//!
+//! struct Score( u32 );
+//! struct Bonus( u32 );
+//!
+//! fn add_bonus( mut score: ResMut<Score>, bonus: Option<Res<Bonus>> ) {
+//!   score.0 += bonus.map_or( 0, |bonus| bonus.0 );
+//! }
+//!
+//! let mut registry = TypeRegistry::new();
+//! registry.insert( Score( 10 ) );
+//! add_bonus.apply( &registry );
+//! registry.insert( Bonus( 5 ) );
+//! add_bonus.apply( &registry );
+//!
+//! assert_eq!( registry.get::<Score>().unwrap().0, 15 );
+//! ```
+
+use std::any::{type_name, Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::system::{FromRegistry, FromRegistryMut, SplitRegistry, SystemArg, TryFromRegistry};
+
+/// A map from a type to a value of that type.
+///
+/// The values are borrowed at runtime, like a [`RefCell`]: a system can take a [`ResMut`] and a [`Res`] of different types,
+/// but it panics taking a [`ResMut`] and any other borrow of the same type.
+#[derive(Default)]
+pub struct TypeRegistry {
+  resources: HashMap<TypeId, RefCell<Box<dyn Any>>>,
+}
+
+impl TypeRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Insert the value of type `T`, returning the previous one
+  pub fn insert<T: 'static>( &mut self, value: T ) -> Option<T> {
+    self.resources.insert( TypeId::of::<T>(), RefCell::new( Box::new( value ) ) )
+      .map( |previous| *previous.into_inner().downcast().expect( "the resources are stored with their type" ) )
+  }
+
+  /// Borrow the value of type `T`.
+  ///
+  /// ## Panics
+  /// If the value is borrowed mutably
+  pub fn get<T: 'static>( &self ) -> Option<Res<'_, T>> {
+    self.resources.get( &TypeId::of::<T>() ).map( |resource| Res::new( resource.borrow() ) )
+  }
+
+  /// Borrow mutably the value of type `T`, without checking at runtime because the registry is borrowed mutably
+  pub fn get_mut<T: 'static>( &mut self ) -> Option<&mut T> {
+    self.resources.get_mut( &TypeId::of::<T>() ).and_then( |resource| resource.get_mut().downcast_mut() )
+  }
+
+  /// Remove the value of type `T`
+  pub fn remove<T: 'static>( &mut self ) -> Option<T> {
+    self.resources.remove( &TypeId::of::<T>() )
+      .map( |resource| *resource.into_inner().downcast().expect( "the resources are stored with their type" ) )
+  }
+
+  /// If there is a value of type `T`
+  pub fn contains<T: 'static>( &self ) -> bool {
+    self.resources.contains_key( &TypeId::of::<T>() )
+  }
+
+  /// How many values are in the registry
+  pub fn len( &self ) -> usize {
+    self.resources.len()
+  }
+
+  pub fn is_empty( &self ) -> bool {
+    self.resources.is_empty()
+  }
+
+  fn try_borrow<T: 'static>( &self ) -> Result<Res<'_, T>, ResourceError> {
+    let resource = self.resources.get( &TypeId::of::<T>() ).ok_or( ResourceError::Missing( type_name::<T>() ) )?;
+    resource.try_borrow().map( Res::new ).map_err( |_| ResourceError::Borrowed( type_name::<T>() ) )
+  }
+
+  fn try_borrow_mut<T: 'static>( &self ) -> Result<ResMut<'_, T>, ResourceError> {
+    let resource = self.resources.get( &TypeId::of::<T>() ).ok_or( ResourceError::Missing( type_name::<T>() ) )?;
+    resource.try_borrow_mut().map( ResMut::new ).map_err( |_| ResourceError::Borrowed( type_name::<T>() ) )
+  }
+}
+
+impl fmt::Debug for TypeRegistry {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    f.debug_struct( "TypeRegistry" ).field( "len", &self.resources.len() ).finish_non_exhaustive()
+  }
+}
+
+/// Why a resource could not be extracted from a [`TypeRegistry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceError {
+  /// There is no value of the type
+  Missing( &'static str ),
+  /// The value is already borrowed, mutably or, to borrow it mutably, at all
+  Borrowed( &'static str ),
+}
+
+impl fmt::Display for ResourceError {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    match self {
+      Self::Missing( name ) => write!( f, "the registry contains no {name}" ),
+      Self::Borrowed( name ) => write!( f, "the {name} in the registry is already borrowed" ),
+    }
+  }
+}
+
+impl Error for ResourceError {}
+
+/// A shared borrow of the value of type `T` in a [`TypeRegistry`]
+pub struct Res<'r, T: 'static> {
+  value: Ref<'r, T>,
+}
+
+impl<'r, T: 'static> Res<'r, T> {
+  fn new( resource: Ref<'r, Box<dyn Any>> ) -> Self {
+    Self { value: Ref::map( resource, |resource| resource.downcast_ref().expect( "the resources are stored with their type" ) ) }
+  }
+}
+
+impl<T: 'static> Deref for Res<'_, T> {
+  type Target = T;
+  fn deref( &self ) -> &T {
+    &self.value
+  }
+}
+
+impl<T: fmt::Debug + 'static> fmt::Debug for Res<'_, T> {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    self.value.fmt( f )
+  }
+}
+
+/// A mutable borrow of the value of type `T` in a [`TypeRegistry`]
+pub struct ResMut<'r, T: 'static> {
+  value: RefMut<'r, T>,
+}
+
+impl<'r, T: 'static> ResMut<'r, T> {
+  fn new( resource: RefMut<'r, Box<dyn Any>> ) -> Self {
+    Self { value: RefMut::map( resource, |resource| resource.downcast_mut().expect( "the resources are stored with their type" ) ) }
+  }
+}
+
+impl<T: 'static> Deref for ResMut<'_, T> {
+  type Target = T;
+  fn deref( &self ) -> &T {
+    &self.value
+  }
+}
+
+impl<T: 'static> DerefMut for ResMut<'_, T> {
+  fn deref_mut( &mut self ) -> &mut T {
+    &mut self.value
+  }
+}
+
+impl<T: fmt::Debug + 'static> fmt::Debug for ResMut<'_, T> {
+  fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    self.value.fmt( f )
+  }
+}
+
+/// Panics if the value is missing or borrowed mutably, use [`System::try_apply`](crate::system::System::try_apply) to get an error
+impl<'r, T: 'static> FromRegistry<'r, TypeRegistry> for Res<'r, T> {
+  fn from_registry( registry: &'r TypeRegistry ) -> Self {
+    registry.try_borrow().unwrap_or_else( |error| panic!( "{error}" ) )
+  }
+}
+
+/// Panics if the value is missing or borrowed, use [`System::try_apply`](crate::system::System::try_apply) to get an error
+impl<'r, T: 'static> FromRegistry<'r, TypeRegistry> for ResMut<'r, T> {
+  fn from_registry( registry: &'r TypeRegistry ) -> Self {
+    registry.try_borrow_mut().unwrap_or_else( |error| panic!( "{error}" ) )
+  }
+}
+
+/// `None` if the value is missing, panics if it's borrowed mutably
+impl<'r, T: 'static> FromRegistry<'r, TypeRegistry> for Option<Res<'r, T>> {
+  fn from_registry( registry: &'r TypeRegistry ) -> Self {
+    registry.contains::<T>().then( || Res::from_registry( registry ) )
+  }
+}
+
+/// `None` if the value is missing, panics if it's borrowed
+impl<'r, T: 'static> FromRegistry<'r, TypeRegistry> for Option<ResMut<'r, T>> {
+  fn from_registry( registry: &'r TypeRegistry ) -> Self {
+    registry.contains::<T>().then( || ResMut::from_registry( registry ) )
+  }
+}
+
+impl<'r, T: 'static> TryFromRegistry<'r, TypeRegistry> for Res<'r, T> {
+  type Error = ResourceError;
+  fn try_from_registry( registry: &'r TypeRegistry ) -> Result<Self, Self::Error> {
+    registry.try_borrow()
+  }
+}
+
+impl<'r, T: 'static> TryFromRegistry<'r, TypeRegistry> for ResMut<'r, T> {
+  type Error = ResourceError;
+  fn try_from_registry( registry: &'r TypeRegistry ) -> Result<Self, Self::Error> {
+    registry.try_borrow_mut()
+  }
+}
+
+impl<'r, T: 'static> TryFromRegistry<'r, TypeRegistry> for Option<Res<'r, T>> {
+  type Error = ResourceError;
+  fn try_from_registry( registry: &'r TypeRegistry ) -> Result<Self, Self::Error> {
+    registry.contains::<T>().then( || registry.try_borrow() ).transpose()
+  }
+}
+
+impl<'r, T: 'static> TryFromRegistry<'r, TypeRegistry> for Option<ResMut<'r, T>> {
+  type Error = ResourceError;
+  fn try_from_registry( registry: &'r TypeRegistry ) -> Result<Self, Self::Error> {
+    registry.contains::<T>().then( || registry.try_borrow_mut() ).transpose()
+  }
+}
+
+/// The resources are already borrowed at runtime, so the registry is its own part and
+/// [`System::apply_mut`](crate::system::System::apply_mut) extracts the same arguments of [`System::apply`](crate::system::System::apply)
+impl<'r> SplitRegistry<'r> for TypeRegistry {
+  type Parts = &'r TypeRegistry;
+  fn split( &'r mut self ) -> Self::Parts {
+    self
+  }
+}
+
+impl<'r, T: 'static> FromRegistryMut<'r, TypeRegistry> for Res<'r, T> {
+  fn from_registry_mut( parts: &mut &'r TypeRegistry ) -> Self {
+    Self::from_registry( parts )
+  }
+}
+
+impl<'r, T: 'static> FromRegistryMut<'r, TypeRegistry> for ResMut<'r, T> {
+  fn from_registry_mut( parts: &mut &'r TypeRegistry ) -> Self {
+    Self::from_registry( parts )
+  }
+}
+
+impl<'r, T: 'static> FromRegistryMut<'r, TypeRegistry> for Option<Res<'r, T>> {
+  fn from_registry_mut( parts: &mut &'r TypeRegistry ) -> Self {
+    Self::from_registry( parts )
+  }
+}
+
+impl<'r, T: 'static> FromRegistryMut<'r, TypeRegistry> for Option<ResMut<'r, T>> {
+  fn from_registry_mut( parts: &mut &'r TypeRegistry ) -> Self {
+    Self::from_registry( parts )
+  }
+}
+
+impl<T: 'static> SystemArg<TypeRegistry> for Res<'_, T> {
+  type Item<'r> = Res<'r, T>;
+}
+
+impl<T: 'static> SystemArg<TypeRegistry> for ResMut<'_, T> {
+  type Item<'r> = ResMut<'r, T>;
+}
+
+impl<T: 'static> SystemArg<TypeRegistry> for Option<Res<'_, T>> {
+  type Item<'r> = Option<Res<'r, T>>;
+}
+
+impl<T: 'static> SystemArg<TypeRegistry> for Option<ResMut<'_, T>> {
+  type Item<'r> = Option<ResMut<'r, T>>;
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use aanyx::host::{Collision, CollisionPolicy, DylibLoader, DylibPluginManager, LoadError, PluginManagerGet, PluginManagerLoad};
+use aanyx::import_plugin;
+use plugin_test_api::Greeter;
+
+fn manager_with_greeter( policy: CollisionPolicy ) -> DylibPluginManager<dyn Greeter> {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) ).with_collision_policy( policy );
+  manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-greeter" ) ) }.unwrap() ).unwrap();
+  manager
+}
+
+fn collision( name: &str, registered_as: &str ) -> Collision {
+  Collision { name: name.to_string(), registered_as: registered_as.to_string() }
+}
+
+fn greet( manager: &DylibPluginManager<dyn Greeter>, plugin: &str ) -> Option<String> {
+  manager.get( &plugin.to_string() ).map( |plugin| plugin.greet( "Ada" ) )
+}
+
+#[test]
+fn reject_fails_the_load_with_the_taken_names() {
+  let mut manager = manager_with_greeter( CollisionPolicy::Reject );
+  let duplicate = common::fixture( "plugin-test-duplicate" );
+
+  let result = manager.load( unsafe { DylibLoader::open( &duplicate ) }.unwrap() );
+  assert!( matches!( result, Err( LoadError::NameCollision { names, .. } ) if names == ["english", "twin"] ) );
+  assert!( manager.library( &duplicate ).is_none() );
+  assert_eq!( greet( &manager, "english" ).as_deref(), Some( "Hello Ada" ) );
+  assert_eq!( greet( &manager, "twin" ), None );
+}
+
+#[test]
+fn replace_keeps_the_last_registered_plugin() {
+  let mut manager = manager_with_greeter( CollisionPolicy::Replace );
+  let duplicate = common::fixture( "plugin-test-duplicate" );
+  let collisions = manager.load_with_collisions( unsafe { DylibLoader::open( &duplicate ) }.unwrap() ).unwrap();
+
+  assert_eq!( collisions, [collision( "english", "english" ), collision( "twin", "twin" )] );
+  assert_eq!( manager.collisions( &duplicate ), Some( collisions.as_slice() ) );
+  assert_eq!( manager.collisions( &common::fixture( "plugin-test-greeter" ) ), Some( [].as_slice() ) );
+
+  assert_eq!( greet( &manager, "english" ).as_deref(), Some( "Howdy Ada" ) );
+  assert_eq!( greet( &manager, "twin" ).as_deref(), Some( "Second Ada" ) );
+  assert_eq!( greet( &manager, "italian" ).as_deref(), Some( "Ciao Ada" ) );
+}
+
+#[test]
+fn suffix_keeps_both_plugins() {
+  let mut manager = manager_with_greeter( CollisionPolicy::Suffix );
+  let duplicate = common::fixture( "plugin-test-duplicate" );
+  let collisions = manager.load_with_collisions( unsafe { DylibLoader::open( &duplicate ) }.unwrap() ).unwrap();
+
+  assert_eq!( collisions, [collision( "english", "english-2" ), collision( "twin", "twin-2" )] );
+  assert_eq!( manager.collisions( &duplicate ), Some( collisions.as_slice() ) );
+
+  let mut plugins: Vec<&str> = manager.plugins().collect();
+  plugins.sort();
+  assert_eq!( plugins, ["english", "english-2", "italian", "twin", "twin-2"] );
+  assert_eq!( greet( &manager, "english" ).as_deref(), Some( "Hello Ada" ) );
+  assert_eq!( greet( &manager, "english-2" ).as_deref(), Some( "Howdy Ada" ) );
+  assert_eq!( greet( &manager, "twin-2" ).as_deref(), Some( "Second Ada" ) );
+}
+
+#[test]
+fn reloading_a_library_does_not_collide_with_itself() {
+  let path = common::fixture( "plugin-test-greeter" );
+  let mut manager = manager_with_greeter( CollisionPolicy::Reject );
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  assert_eq!( greet( &manager, "english" ).as_deref(), Some( "Hello Ada" ) );
+}
@@ -0,0 +1,28 @@
+//! Helpers shared by the integration tests that need real plugins.
+//! The fixture plugins live in the `plugin-test` workspace and are compiled on demand.
+
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Build the `plugin-test` workspace and return the directory containing the compiled libraries
+pub fn fixtures_dir() -> &'static Path {
+  static DIR: OnceLock<PathBuf> = OnceLock::new();
+  DIR.get_or_init( || {
+    let target_dir = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "plugin-test" );
+    let status = Command::new( std::env::var( "CARGO" ).unwrap_or_else( |_| String::from("cargo") ) )
+      .arg( "build" )
+      .arg( "--manifest-path" ).arg( Path::new( env!("CARGO_MANIFEST_DIR") ).join( "plugin-test/Cargo.toml" ) )
+      .arg( "--target-dir" ).arg( &target_dir )
+      .status()
+      .expect( "cargo should be available to build the fixture plugins" );
+    assert!( status.success(), "the fixture plugins failed to build" );
+    target_dir.join( "debug" )
+  })
+}
+
+/// The path of the compiled fixture library for the crate `name`
+pub fn fixture( name: &str ) -> PathBuf {
+  fixtures_dir().join( format!( "{DLL_PREFIX}{}{DLL_SUFFIX}", name.replace( '-', "_" ) ) )
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use aanyx::host::{DylibLoader, DylibPluginManager, LoadError, PluginManagerGet, PluginManagerLoad, PluginManagerReload, PluginManagerUnload, UnloadError};
+use aanyx::import_plugin;
+use plugin_test_api::Greeter;
+
+fn loaders() -> [DylibLoader; 2] {
+  [
+    unsafe { DylibLoader::open( common::fixture( "plugin-test-dependent" ) ) }.unwrap(),
+    unsafe { DylibLoader::open( common::fixture( "plugin-test-base" ) ) }.unwrap(),
+  ]
+}
+
+#[test]
+fn load_fails_when_dependencies_are_missing() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-dependent" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( &error, LoadError::DependencyNotSatisfied { plugin, dependency, requirement }
+    if plugin == "plugin-test-dependent" && dependency == "plugin-test-base" && requirement == "^0.1" ) );
+  assert!( manager.get( &String::from("dependent") ).is_none() );
+}
+
+#[test]
+fn load_all_loads_dependencies_first() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+
+  let results = manager.load_all( loaders() );
+  assert!( results.iter().all( Result::is_ok ), "{results:?}" );
+  assert_eq!( manager.get( &String::from("dependent") ).unwrap().greet( "Alice" ), "Welcome back Alice" );
+  assert_eq!( manager.library_with_id( "plugin-test-base" ), Some( common::fixture( "plugin-test-base" ).as_path() ) );
+}
+
+#[test]
+fn load_all_detects_cycles() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( Cyclic ) );
+
+  for result in manager.load_all( loaders() ) {
+    assert!( matches!( &result, Err( LoadError::DependencyCycle( plugins ) ) if plugins == &["plugin-test-base", "plugin-test-dependent"] ) );
+  }
+  assert_eq!( manager.libraries().count(), 0 );
+}
+
+#[test]
+fn load_all_checks_the_version_requirements() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( Outdated ) );
+
+  let results = manager.load_all( loaders() );
+  assert!( matches!( &results[0], Err( LoadError::DependencyNotSatisfied { requirement, .. } ) if requirement == "^2" ) );
+  assert!( results[1].is_ok() );
+}
+
+#[test]
+fn unload_refuses_to_unload_required_libraries() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load_all( loaders() );
+  let base = common::fixture( "plugin-test-base" );
+  let dependent = common::fixture( "plugin-test-dependent" );
+
+  assert_eq!( manager.unload( &base ), Err( UnloadError::Required { dependents: vec![dependent.clone()] } ) );
+  assert!( manager.get( &String::from("base") ).is_some() );
+
+  manager.unload( &dependent ).unwrap();
+  manager.unload( &base ).unwrap();
+  assert_eq!( manager.unload( &base ), Err( UnloadError::NotLoaded ) );
+}
+
+#[test]
+fn unload_cascade_unloads_the_dependents_first() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load_all( loaders() );
+  let base = common::fixture( "plugin-test-base" );
+
+  assert_eq!( manager.unload_cascade( &base ), Ok( vec![common::fixture( "plugin-test-dependent" ), base.clone()] ) );
+  assert_eq!( manager.libraries().count(), 0 );
+  assert_eq!( manager.unload_cascade( &base ), Err( UnloadError::NotLoaded ) );
+}
+
+#[test]
+fn reload_refuses_to_close_a_cycle() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( Rebuilt ) );
+  let results = manager.load_all( loaders() );
+  assert!( results.iter().all( Result::is_ok ), "{results:?}" );
+  let base = common::fixture( "plugin-test-base" );
+
+  let rebuilt = unsafe { DylibLoader::open( common::fixture( "plugin-test-duplicate" ) ) }.unwrap();
+  let result = manager.reload_as_new( rebuilt, &base );
+  assert!( matches!( &result, Err( LoadError::DependencyCycle( plugins ) ) if plugins == &["plugin-test-base", "plugin-test-dependent"] ), "{result:?}" );
+  assert!( manager.get( &String::from("rebuilt") ).is_none() );
+  // Without the old build, `plugin-test-dependent` has lost its dependency too
+  assert_eq!( manager.libraries().count(), 0 );
+}
+
+#[test]
+fn reload_unloads_the_dependents_no_longer_satisfied() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load_all( loaders() );
+  let base = common::fixture( "plugin-test-base" );
+
+  // The greeter has another id, so `plugin-test-dependent` loses its dependency
+  manager.reload_as_new( unsafe { DylibLoader::open( common::fixture( "plugin-test-greeter" ) ) }.unwrap(), &base ).unwrap();
+  assert_eq!( manager.libraries().collect::<Vec<_>>(), [common::fixture( "plugin-test-greeter" ).as_path()] );
+  assert!( manager.get( &String::from("dependent") ).is_none() );
+  assert_eq!( manager.get( &String::from("english") ).unwrap().greet( "Ada" ), "Hello Ada" );
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use aanyx::host::{discover, Discovered, DiscoveryError, DylibPluginManager, LoadError, PluginManagerGet};
+use aanyx::import_plugin;
+use plugin_test_api::Greeter;
+
+#[test]
+fn discover_loads_only_the_libraries_exporting_the_declaration() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  let report = unsafe { discover( &mut manager, common::fixtures_dir(), import_plugin!( dyn Greeter ) ) }.unwrap();
+
+  let outcome_of = |name: &str| report.iter().find( |(path, _)| *path == common::fixture( name ) ).map( |(_, outcome)| outcome );
+  assert!( matches!( outcome_of( "plugin-test-greeter" ), Some( Discovered::Loaded ) ) );
+  assert!( matches!( outcome_of( "plugin-test-mismatch" ), Some( Discovered::Skipped ) ) );
+  assert!( matches!( outcome_of( "plugin-test-panicking" ), Some( Discovered::Failed( DiscoveryError::Load( LoadError::PluginPanicked { .. } ) ) ) ) );
+  assert!( report.iter().all( |(path, _)| path.extension() == Some( std::env::consts::DLL_EXTENSION.as_ref() ) ) );
+  assert_eq!( manager.get( &String::from("english") ).unwrap().greet( "Alice" ), "Hello Alice" );
+}
+
+#[test]
+fn discover_reports_the_files_that_cannot_be_opened() {
+  let dir = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "discovery" );
+  fs::create_dir_all( &dir ).unwrap();
+  let broken = dir.join( format!( "broken.{}", std::env::consts::DLL_EXTENSION ) );
+  fs::write( &broken, b"not a library" ).unwrap();
+  fs::write( dir.join( "notes.txt" ), b"not a library either" ).unwrap();
+
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  let report = unsafe { discover( &mut manager, &dir, import_plugin!( dyn Greeter ) ) }.unwrap();
+
+  assert_eq!( report.len(), 1 );
+  assert_eq!( report[0].0, broken );
+  assert!( matches!( report[0].1, Discovered::Failed( DiscoveryError::Open( _ ) ) ) );
+}
+
+#[test]
+fn discover_fails_when_the_directory_is_missing() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  assert!( unsafe { discover( &mut manager, common::fixtures_dir().join( "missing" ), import_plugin!( dyn Greeter ) ) }.is_err() );
+}
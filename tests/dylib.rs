@@ -0,0 +1,205 @@
+mod common;
+
+use std::path::PathBuf;
+
+use aanyx::host::{DylibLoader, DylibPluginManager, LoadError, PluginManagerGet, PluginManagerLoad, PluginManagerReload, PluginManagerUnload};
+use aanyx::import_plugin;
+use aanyx::plugin::{HostContext, Interface, DECLARATION_VERSION};
+use plugin_test_api::{Events, Greeter, Handler};
+
+fn greeter_manager() -> (DylibPluginManager<dyn Greeter>, PathBuf) {
+  let path = common::fixture( "plugin-test-greeter" );
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  (manager, path)
+}
+
+#[test]
+fn load_registers_every_plugin_of_the_library() {
+  let (manager, path) = greeter_manager();
+
+  assert_eq!( manager.get( &String::from("english") ).unwrap().greet( "Alice" ), "Hello Alice" );
+  assert_eq!( manager.get( &String::from("italian") ).unwrap().greet( "Bob" ), "Ciao Bob" );
+  assert!( manager.get( &String::from("french") ).is_none() );
+  assert_eq!( manager.library_of( "english" ), Some( path.as_path() ) );
+
+  let mut plugins: Vec<_> = manager.plugins().collect();
+  plugins.sort();
+  assert_eq!( plugins, ["english", "italian"] );
+}
+
+#[test]
+fn load_plugins_exported_with_the_attribute() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-attribute" ) ) }.unwrap() ).unwrap();
+
+  assert_eq!( manager.get( &String::from("attribute") ).unwrap().greet( "Alice" ), "Greetings Alice" );
+  assert_eq!( manager.library_with_id( "plugin-test-attribute" ), Some( common::fixture( "plugin-test-attribute" ).as_path() ) );
+}
+
+#[test]
+fn metadata_is_read_without_registering_the_plugins() {
+  let path = common::fixture( "plugin-test-greeter" );
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  let library = unsafe { DylibLoader::open( &path ) }.unwrap();
+
+  let metadata = manager.metadata( &library ).unwrap();
+  assert_eq!( metadata.name, "Greeter" );
+  assert_eq!( metadata.version, "0.1.0" );
+  assert_eq!( metadata.authors, ["Alice", "Bob"] );
+  assert_eq!( metadata.description, "Greets in english and italian" );
+  assert_eq!( metadata.license, "MIT" );
+  assert_eq!( metadata.capabilities, ["english", "italian"] );
+  assert_eq!( manager.plugins().count(), 0 );
+
+  manager.load( library ).unwrap();
+  assert_eq!( manager.library_metadata( &path ), Some( &metadata ) );
+
+  // Without the option, the metadata is read from the package of the plugin
+  let attribute = unsafe { DylibLoader::open( common::fixture( "plugin-test-attribute" ) ) }.unwrap();
+  let metadata = manager.metadata( &attribute ).unwrap();
+  assert_eq!( metadata.name, "plugin-test-attribute" );
+  assert!( metadata.authors.is_empty() && metadata.capabilities.is_empty() );
+}
+
+#[test]
+fn load_rejects_the_libraries_panicking_in_register() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-panicking" ) ) }.unwrap() ).unwrap_err();
+
+  assert!( matches!( &error, LoadError::PluginPanicked { plugin, message } if plugin == "plugin-test-panicking" && message == "cannot register late" ) );
+  assert_eq!( manager.plugins().count(), 0 );
+  assert_eq!( manager.libraries().count(), 0 );
+
+  // The host is still alive and can load other libraries
+  manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-greeter" ) ) }.unwrap() ).unwrap();
+  assert_eq!( manager.get( &String::from("english") ).unwrap().greet( "Alice" ), "Hello Alice" );
+}
+
+#[test]
+fn plugins_use_the_services_of_the_host_context() {
+  let path = common::fixture( "plugin-test-context" );
+  let context = HostContext::new().with_service( "greeting", String::from("Welcome") ).with_service( "events", Events::default() );
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) ).with_context( context );
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+
+  assert_eq!( manager.get( &String::from("contextual") ).unwrap().greet( "Alice" ), "Welcome Alice" );
+  let events = |manager: &DylibPluginManager<dyn Greeter>| unsafe { manager.context().service::<Events>( "events" ) }.unwrap().0.lock().unwrap().clone();
+  assert_eq!( events( &manager ), ["registering with Welcome"] );
+
+  // A service with another type is not returned
+  manager.context_mut().insert_service( "greeting", 42 );
+  manager.reload( &path ).unwrap();
+  assert_eq!( manager.get( &String::from("contextual") ).unwrap().greet( "Bob" ), "Hi Bob" );
+  assert_eq!( events( &manager ), ["registering with Welcome", "registering with Hi"] );
+}
+
+#[test]
+fn load_plugin_types_with_paths_and_generic_arguments() {
+  let path = common::fixture( "plugin-test-paths" );
+
+  let mut greeters = DylibPluginManager::<dyn plugin_test_api::v2::Greeter>::new( import_plugin!( dyn plugin_test_api::v2::Greeter ) );
+  greeters.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  assert_eq!( greeters.get( &String::from("repeat") ).unwrap().greet( "Bo", 2 ), "Hello BoBo" );
+
+  let mut numbers = DylibPluginManager::<dyn Handler<u32>>::new( import_plugin!( dyn plugin_test_api::Handler<u32> ) );
+  numbers.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  assert_eq!( numbers.get( &String::from("number") ).unwrap().handle( 7 ), "number 7" );
+
+  let mut texts = DylibPluginManager::<dyn Handler<String>>::new( import_plugin!( dyn plugin_test_api::Handler<String> ) );
+  texts.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  assert_eq!( texts.get( &String::from("text") ).unwrap().handle( String::from("seven") ), "text seven" );
+
+  // The path is part of the name, so an imported trait must be written as in the plugin
+  let mut imported = DylibPluginManager::<dyn Handler<u32>>::new( import_plugin!( dyn Handler<u32> ) );
+  assert!( matches!( imported.load( unsafe { DylibLoader::open( &path ) }.unwrap() ), Err( LoadError::SymbolNotFound { .. } ) ) );
+}
+
+#[test]
+fn load_checks_the_interface_fingerprint() {
+  let fingerprint = <dyn Greeter as Interface>::FINGERPRINT;
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) ).with_fingerprint( fingerprint );
+  manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-greeter" ) ) }.unwrap() ).unwrap();
+
+  // Same name of the trait, but another signature
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-legacy" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( error, LoadError::FingerprintMismatch { plugin, host } if plugin != 0 && host == fingerprint ) );
+  // No fingerprint at all
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-attribute" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( error, LoadError::FingerprintMismatch { plugin: 0, .. } ) );
+  assert!( manager.get( &String::from("legacy") ).is_none() );
+}
+
+#[test]
+fn load_fails_when_the_declaration_is_missing() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Farewell ) );
+
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-greeter" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( &error, LoadError::SymbolNotFound { symbol, .. } if symbol == "plugin_declaration_dyn_Farewell" ) );
+  assert_eq!( manager.plugins().count(), 0 );
+  assert_eq!( manager.libraries().count(), 0 );
+}
+
+#[test]
+fn open_fails_for_missing_libraries() {
+  let error = unsafe { DylibLoader::open( common::fixtures_dir().join( "missing-library" ) ) }.err().unwrap();
+  assert!( matches!( error, LoadError::LibraryOpenFailed( _ ) ) );
+}
+
+#[test]
+fn load_rejects_plugins_compiled_with_another_rustc() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( OtherRustc ) );
+
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-mismatch" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( &error, LoadError::RustcMismatch { plugin, host } if plugin == "1.0.0" && host == aanyx::RUSTC_VERSION ) );
+  assert_eq!( manager.libraries().count(), 0 );
+}
+
+#[test]
+fn load_rejects_plugins_compiled_against_another_core_version() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( OtherCore ) );
+
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-mismatch" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( &error, LoadError::CoreVersionMismatch { plugin, host } if plugin == "0.0.1" && host == aanyx::CORE_VERSION ) );
+  assert_eq!( manager.libraries().count(), 0 );
+}
+
+#[test]
+fn load_rejects_declarations_with_another_layout() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( OtherLayout ) );
+
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-mismatch" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( error, LoadError::DeclarationMismatch { plugin: 0, host: DECLARATION_VERSION } ) );
+  assert_eq!( manager.libraries().count(), 0 );
+}
+
+#[test]
+fn compatibility_policy_decides_which_core_versions_are_accepted() {
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( OtherCore ) )
+    .with_policy( |plugin: &str, _host: &str| plugin == "0.0.1" );
+
+  manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-mismatch" ) ) }.unwrap() ).unwrap();
+  assert_eq!( manager.get( &String::from("old") ).unwrap().greet( "Alice" ), "Good morrow Alice" );
+}
+
+#[test]
+fn unload_drops_the_plugins_of_the_library() {
+  let (mut manager, path) = greeter_manager();
+
+  assert!( manager.unload( &path ).is_ok() );
+  assert!( manager.get( &String::from("english") ).is_none() );
+  assert_eq!( manager.libraries().count(), 0 );
+  assert!( manager.unload( &path ).is_err() );
+}
+
+#[test]
+fn reload_registers_the_plugins_again() {
+  let (mut manager, path) = greeter_manager();
+
+  manager.reload( &path ).unwrap();
+  assert_eq!( manager.get( &String::from("english") ).unwrap().greet( "Alice" ), "Hello Alice" );
+
+  manager.reload_as_new( unsafe { DylibLoader::open( &path ) }.unwrap(), &path ).unwrap();
+  assert_eq!( manager.get( &String::from("italian") ).unwrap().greet( "Bob" ), "Ciao Bob" );
+  assert_eq!( manager.libraries().count(), 1 );
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use aanyx::host::{inspect, InspectError};
+use aanyx::host::inspect::plugin_type_of;
+use aanyx::import_plugin;
+
+#[test]
+fn inspect_lists_the_declarations_with_their_metadata() {
+  let inspection = inspect( common::fixture( "plugin-test-greeter" ) ).unwrap();
+
+  assert_eq!( inspection.declarations.len(), 1 );
+  let declaration = inspection.declaration( import_plugin!( dyn Greeter ) ).unwrap();
+  assert_eq!( declaration.plugin_type, "dyn Greeter" );
+  let metadata = declaration.metadata.as_ref().unwrap();
+  assert_eq!( metadata.name, "Greeter" );
+  assert_eq!( metadata.authors, ["Alice", "Bob"] );
+  assert_eq!( metadata.capabilities, ["english", "italian"] );
+}
+
+#[test]
+fn inspect_recovers_paths_and_generic_arguments() {
+  let inspection = inspect( common::fixture( "plugin-test-paths" ) ).unwrap();
+
+  let types: Vec<&str> = inspection.declarations.iter().map( |declaration| declaration.plugin_type.as_str() ).collect();
+  assert_eq!( types, ["dyn plugin_test_api::Handler<String>", "dyn plugin_test_api::Handler<u32>", "dyn plugin_test_api::v2::Greeter"] );
+  assert!( inspection.declarations.iter().all( |declaration| declaration.metadata.as_ref().unwrap().name == "plugin-test-paths" ) );
+  assert!( inspection.declaration( import_plugin!( dyn plugin_test_api::Handler<u32> ) ).is_some() );
+}
+
+#[test]
+fn inspect_finds_declarations_written_by_hand_without_metadata() {
+  let inspection = inspect( common::fixture( "plugin-test-mismatch" ) ).unwrap();
+
+  let types: Vec<&str> = inspection.declarations.iter().map( |declaration| declaration.plugin_type.as_str() ).collect();
+  assert_eq!( types, ["OtherCore", "OtherLayout", "OtherRustc"] );
+  assert!( inspection.declarations.iter().all( |declaration| declaration.metadata.is_none() ) );
+}
+
+#[test]
+fn inspect_rejects_files_that_are_not_elf() {
+  let path = std::env::temp_dir().join( format!( "aanyx-inspect-{}.so", std::process::id() ) );
+  std::fs::write( &path, b"not a library" ).unwrap();
+  let result = inspect( &path );
+  std::fs::remove_file( &path ).unwrap();
+
+  assert!( matches!( result, Err( InspectError::NotElf ) ) );
+  assert!( matches!( inspect( path ), Err( InspectError::Io( _ ) ) ) );
+}
+
+// A 64 bits little endian header whose sections start at 64, with their count, 2^40, stored in the first section
+fn crafted_header( entry_size: u16 ) -> Vec<u8> {
+  let mut data = vec![0; 128];
+  data[..6].copy_from_slice( b"\x7fELF\x02\x01" );
+  data[0x28..0x30].copy_from_slice( &64u64.to_le_bytes() );
+  data[0x3a..0x3c].copy_from_slice( &entry_size.to_le_bytes() );
+  data[64 + 32..64 + 40].copy_from_slice( &( 1u64 << 40 ).to_le_bytes() );
+  data
+}
+
+#[test]
+fn inspect_rejects_section_headers_outside_of_the_file() {
+  for (entry_size, reason) in [(0, "section header size"), (64, "section headers")] {
+    let path = std::env::temp_dir().join( format!( "aanyx-inspect-{}-{entry_size}.so", std::process::id() ) );
+    std::fs::write( &path, crafted_header( entry_size ) ).unwrap();
+    let result = inspect( &path );
+    std::fs::remove_file( &path ).unwrap();
+
+    assert!( matches!( result, Err( InspectError::Malformed( malformed ) ) if malformed == reason ), "{entry_size}: {result:?}" );
+  }
+}
+
+#[test]
+fn plugin_type_of_reverses_the_escapes_of_non_ascii_types() {
+  let symbol = std::str::from_utf8( import_plugin!( dyn api::Grüßer<ā> ) ).unwrap();
+  assert_eq!( symbol, "plugin_declaration_dyn_api_3a_3aGr_c3_bc_c3_9fer_3c_c4_81_3e" );
+  assert_eq!( plugin_type_of( symbol ).unwrap(), "dyn api::Grüßer<ā>" );
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use aanyx::host::{DylibLoader, DylibPluginManager, PluginManagerLoad, PluginManagerReload, PluginManagerUnload};
+use aanyx::import_plugin;
+use plugin_test_api::Greeter;
+
+// The greeter plugin writes its lifecycle events in the file named by `AANYX_LIFECYCLE_LOG`.
+// Everything is checked in a single test, because the environment is shared by all the tests of this file.
+#[test]
+fn manager_calls_the_lifecycle_hooks() {
+  let log = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "lifecycle.log" );
+  let _ = fs::remove_file( &log );
+  std::env::set_var( "AANYX_LIFECYCLE_LOG", &log );
+  let events = || fs::read_to_string( &log ).unwrap_or_default().lines().map( String::from ).collect::<Vec<_>>();
+  let path = common::fixture( "plugin-test-greeter" );
+
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  assert_eq!( events(), ["register", "init"] );
+
+  manager.reload( &path ).unwrap();
+  assert_eq!( events()[2..], ["before_reload", "shutdown", "register", "init"] );
+
+  // The old library is shut down before the new one registers its plugins
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  assert_eq!( events()[6..], ["shutdown", "register", "init"] );
+
+  manager.unload( &path ).unwrap();
+  assert_eq!( events()[9..], ["shutdown"] );
+
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+  drop( manager );
+  assert_eq!( events()[10..], ["register", "init", "shutdown"] );
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aanyx::host::{DylibLoader, DylibPluginManager, PluginManagerGet, PluginManagerLoad, PluginManagerReload, PluginManagerUnload};
+use aanyx::import_plugin;
+use plugin_test_api::Greeter;
+
+fn greeter_copy( test: &str ) -> PathBuf {
+  let dir = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "shadow" ).join( test );
+  fs::create_dir_all( &dir ).unwrap();
+  let path = dir.join( common::fixture( "plugin-test-greeter" ).file_name().unwrap() );
+  fs::copy( common::fixture( "plugin-test-greeter" ), &path ).unwrap();
+  path
+}
+
+#[test]
+fn shadow_copy_is_deleted_on_unload() {
+  let path = greeter_copy( "unload" );
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load( unsafe { DylibLoader::open_shadowed( &path ) }.unwrap() ).unwrap();
+
+  let shadow = manager.library( &path ).unwrap().shadow_path().unwrap().to_path_buf();
+  assert_ne!( shadow, path );
+  assert!( shadow.file_name().unwrap().to_str().unwrap().ends_with( path.file_name().unwrap().to_str().unwrap() ) );
+  assert!( shadow.exists() );
+  assert_eq!( manager.library_of( "english" ), Some( path.as_path() ) );
+
+  manager.unload( &path ).unwrap();
+  assert!( !shadow.exists() );
+  assert!( path.exists() );
+}
+
+#[test]
+fn original_can_be_overwritten_while_loaded() {
+  let path = greeter_copy( "overwrite" );
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load( unsafe { DylibLoader::open_shadowed( &path ) }.unwrap() ).unwrap();
+  let first_shadow = manager.library( &path ).unwrap().shadow_path().unwrap().to_path_buf();
+
+  // Overwrite the file in place, as `cargo build` would do
+  fs::copy( common::fixture( "plugin-test-greeter" ), &path ).unwrap();
+  assert_eq!( manager.get( &String::from("english") ).unwrap().greet( "Alice" ), "Hello Alice" );
+
+  manager.reload( &path ).unwrap();
+  let library = manager.library( &path ).unwrap();
+  assert!( library.is_shadowed(), "reload keeps using shadow copies" );
+  assert_ne!( library.shadow_path().unwrap(), first_shadow );
+  assert!( !first_shadow.exists() );
+  assert_eq!( manager.get( &String::from("italian") ).unwrap().greet( "Bob" ), "Ciao Bob" );
+}
+
+#[test]
+fn open_shadowed_fails_for_missing_libraries() {
+  assert!( unsafe { DylibLoader::open_shadowed( common::fixtures_dir().join( "missing-library" ) ) }.is_err() );
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use aanyx::host::{DylibLoader, DylibPluginManager, LoadError, PluginManagerGet, PluginManagerLoad};
+use aanyx::import_plugin;
+use plugin_test_api::{Calculator, CalculatorFfi};
+
+struct Local {
+  name: String,
+}
+
+impl Calculator for Local {
+  fn add( &self, a: i64, b: i64 ) -> i64 { a * b }
+  fn sum( &self, values: &[f64] ) -> f64 { values.len() as f64 }
+  fn describe( &self, name: &str ) -> String { format!("{name} is {}", self.name) }
+  fn rename( &mut self, name: String ) { self.name = name }
+}
+
+#[test]
+fn ffi_objects_forward_the_calls_to_the_plugin() {
+  let mut calculator = CalculatorFfi::new( Box::new( Local { name: String::from("local") } ) );
+
+  assert_eq!( calculator.add( 2, 3 ), 6 );
+  assert_eq!( calculator.sum( &[1.5, 2.0] ), 2.0 );
+  assert_eq!( calculator.describe( "It" ), "It is local" );
+  calculator.rename( String::from("renamed") );
+  assert_eq!( calculator.describe( "It" ), "It is renamed" );
+}
+
+#[test]
+fn load_stable_plugins_through_the_vtable() {
+  let mut manager = DylibPluginManager::<dyn Calculator>::new( import_plugin!( dyn Calculator ) ).with_stable_abi();
+  manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-stable" ) ) }.unwrap() ).unwrap();
+
+  let calculator = manager.get( &String::from("calculator") ).unwrap();
+  assert_eq!( calculator.add( 2, 3 ), 5 );
+  assert_eq!( calculator.sum( &[1.5, 2.0] ), 3.5 );
+  assert_eq!( calculator.describe( "It" ), "It is a calculator" );
+}
+
+#[test]
+fn stable_plugins_skip_the_rustc_check() {
+  let mut manager = DylibPluginManager::<dyn Calculator>::new( import_plugin!( OtherRustc ) ).with_stable_abi();
+  manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-stable" ) ) }.unwrap() ).unwrap();
+  assert_eq!( manager.get( &String::from("calculator") ).unwrap().add( 1, 1 ), 2 );
+
+  let mut manager = DylibPluginManager::<dyn Calculator>::new( import_plugin!( OtherRustc ) );
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-stable" ) ) }.unwrap() ).unwrap_err();
+  assert!( matches!( &error, LoadError::RustcMismatch { plugin, .. } if plugin == "1.0.0" ) );
+  assert_eq!( manager.libraries().count(), 0 );
+}
+
+#[test]
+fn stable_plugins_report_panics_in_register() {
+  let mut manager = DylibPluginManager::<dyn Calculator>::new( import_plugin!( Panicking ) ).with_stable_abi();
+  let error = manager.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-stable" ) ) }.unwrap() ).unwrap_err();
+
+  assert!( matches!( &error, LoadError::PluginPanicked { message, .. } if message == "the calculator is broken" ) );
+  assert_eq!( manager.plugins().count(), 0 );
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use aanyx::host::{DylibLoader, DylibPluginManager, LoadError, PluginManagerGet, PluginManagerLoad, PluginManagerReload};
+use aanyx::import_plugin;
+use plugin_test_api::Counter;
+
+fn increment( manager: &DylibPluginManager<dyn Counter> ) -> u64 {
+  manager.get( &String::from("counter") ).unwrap().increment()
+}
+
+#[test]
+fn reload_keeps_the_state() {
+  let path = common::fixture( "plugin-test-counter" );
+  let mut manager = DylibPluginManager::<dyn Counter>::new( import_plugin!( dyn Counter ) );
+  manager.load( unsafe { DylibLoader::open_shadowed( &path ) }.unwrap() ).unwrap();
+  increment( &manager );
+  increment( &manager );
+
+  manager.reload( &path ).unwrap();
+  assert_eq!( increment( &manager ), 3 );
+}
+
+#[test]
+fn new_build_migrates_the_state_of_an_older_build() {
+  let v1 = common::fixture( "plugin-test-counter" );
+  let v2 = common::fixture( "plugin-test-counter-v2" );
+  let mut manager = DylibPluginManager::<dyn Counter>::new( import_plugin!( dyn Counter ) );
+  manager.load( unsafe { DylibLoader::open_shadowed( &v1 ) }.unwrap() ).unwrap();
+  increment( &manager );
+
+  manager.reload_as_new( unsafe { DylibLoader::open_shadowed( &v2 ) }.unwrap(), &v1 ).unwrap();
+  assert_eq!( manager.libraries().collect::<Vec<_>>(), [v2.as_path()] );
+  assert_eq!( increment( &manager ), 2 );
+}
+
+#[test]
+fn rejected_state_unloads_the_new_build() {
+  let v1 = common::fixture( "plugin-test-counter" );
+  let v2 = common::fixture( "plugin-test-counter-v2" );
+  let mut manager = DylibPluginManager::<dyn Counter>::new( import_plugin!( dyn Counter ) );
+  manager.load( unsafe { DylibLoader::open_shadowed( &v2 ) }.unwrap() ).unwrap();
+  increment( &manager );
+
+  let result = manager.reload_as_new( unsafe { DylibLoader::open_shadowed( &v1 ) }.unwrap(), &v2 );
+  assert!( matches!( result, Err( LoadError::StateRejected { plugin, .. } ) if plugin == "plugin-test-counter" ) );
+  assert!( manager.get( &String::from("counter") ).is_none() );
+  assert_eq!( manager.libraries().count(), 0 );
+}
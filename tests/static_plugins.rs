@@ -0,0 +1,48 @@
+mod common;
+
+// Nothing of the crate is used, link its declaration anyway
+extern crate plugin_test_switch;
+
+use aanyx::host::{DylibLoader, DylibPluginManager, LoadError, PluginManagerGet, PluginManagerLoad, StaticPluginManager};
+use aanyx::import_plugin;
+use plugin_test_api::{Counter, Greeter};
+
+#[test]
+fn static_plugins_are_registered_like_dynamic_ones() {
+  let manager = StaticPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+
+  assert_eq!( manager.get( &String::from("static-english") ).unwrap().greet( "Alice" ), "Hello Alice" );
+  assert_eq!( manager.get( &String::from("static-italian") ).unwrap().greet( "Bob" ), "Ciao Bob" );
+  assert_eq!( manager.libraries().collect::<Vec<_>>(), ["linked-base", "linked-app"] );
+
+  let mut errors: Vec<_> = manager.errors().iter().map( ToString::to_string ).collect();
+  errors.sort();
+  assert_eq!( errors, [
+    "the plugin linked-panicking panicked while registering: static panic",
+    "the plugin linked-twin registered names already taken: static-english",
+  ]);
+  assert!( manager.errors().iter().any( |error| matches!( error, LoadError::PluginPanicked { .. } ) ) );
+
+  drop( manager );
+  assert_eq!( plugin_test_linked::events(), ["register base", "init base", "register app", "shutdown app", "shutdown base"] );
+}
+
+#[test]
+fn static_plugins_are_found_by_the_declaration_name() {
+  let manager = StaticPluginManager::<dyn Greeter>::new( import_plugin!( dyn OtherGreeter ) );
+  assert_eq!( manager.plugins().count(), 0 );
+  assert!( manager.errors().is_empty() );
+}
+
+#[test]
+fn the_same_crate_is_linked_with_the_feature_or_loaded_without() {
+  // The crate is a dependency of the tests, with the feature
+  let linked = StaticPluginManager::<dyn Counter>::new( import_plugin!( dyn Counter ) );
+  assert_eq!( linked.libraries().collect::<Vec<_>>(), ["switch"] );
+  assert_eq!( linked.get( &String::from("switch") ).unwrap().increment(), 1 );
+
+  // The fixture is built without the feature
+  let mut loaded = DylibPluginManager::<dyn Counter>::new( import_plugin!( dyn Counter ) );
+  loaded.load( unsafe { DylibLoader::open( common::fixture( "plugin-test-switch" ) ) }.unwrap() ).unwrap();
+  assert_eq!( loaded.get( &String::from("switch") ).unwrap().increment(), 1 );
+}
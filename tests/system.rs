@@ -0,0 +1,112 @@
+use aanyx::system::{BoxedSystem, ExtractError, IntoSystem, Res, ResMut, ResourceError, System, TypeRegistry};
+
+struct Name( String );
+struct Visits( u32 );
+
+fn visit( name: Res<Name>, mut visits: ResMut<Visits> ) -> String {
+  visits.0 += 1;
+  format!( "{} visited {} times", name.0, visits.0 )
+}
+
+fn registry() -> TypeRegistry {
+  let mut registry = TypeRegistry::new();
+  registry.insert( Name( String::from("Ada") ) );
+  registry.insert( Visits( 0 ) );
+  registry
+}
+
+#[test]
+fn systems_write_into_the_registry() {
+  let registry = registry();
+
+  assert_eq!( visit.apply( &registry ), "Ada visited 1 times" );
+  assert_eq!( visit.apply( &registry ), "Ada visited 2 times" );
+  assert_eq!( registry.get::<Visits>().unwrap().0, 2 );
+}
+
+#[test]
+fn the_registry_holds_one_value_for_each_type() {
+  let mut registry = registry();
+
+  assert!( registry.contains::<Name>() );
+  assert_eq!( registry.insert( Visits( 7 ) ).map( |previous| previous.0 ), Some( 0 ) );
+  registry.get_mut::<Visits>().unwrap().0 += 1;
+  assert_eq!( registry.remove::<Visits>().map( |visits| visits.0 ), Some( 8 ) );
+  assert!( !registry.contains::<Visits>() );
+  assert!( registry.get::<Visits>().is_none() );
+  assert_eq!( registry.len(), 1 );
+}
+
+#[test]
+fn optional_resources_may_be_missing() {
+  fn greet( name: Option<Res<Name>> ) -> String {
+    name.map_or( String::from("Hello"), |name| format!( "Hello {}", name.0 ) )
+  }
+
+  let mut registry = registry();
+  assert_eq!( greet.apply( &registry ), "Hello Ada" );
+  registry.remove::<Name>();
+  assert_eq!( greet.apply( &registry ), "Hello" );
+}
+
+#[test]
+fn try_apply_reports_the_missing_and_the_aliased_resources() {
+  let mut registry = registry();
+  registry.remove::<Visits>();
+  assert_eq!( visit.try_apply( &registry ), Err( ExtractError {
+    index: 1,
+    type_name: std::any::type_name::<ResMut<Visits>>(),
+    reason: ResourceError::Missing( std::any::type_name::<Visits>() ).to_string(),
+  }));
+
+  fn rename( _: Res<Name>, _: ResMut<Name> ) {}
+  let error = rename.try_apply( &registry ).unwrap_err();
+  assert_eq!( error.index, 1 );
+  assert_eq!( error.reason, ResourceError::Borrowed( std::any::type_name::<Name>() ).to_string() );
+}
+
+#[test]
+fn apply_mut_extracts_the_resources_from_a_registry_borrowed_mutably() {
+  fn rename( mut name: ResMut<Name>, visits: Option<Res<Visits>> ) {
+    name.0 = format!( "{} ({})", name.0, visits.map_or( 0, |visits| visits.0 ) );
+  }
+
+  let mut registry = registry();
+  assert_eq!( visit.apply_mut( &mut registry ), "Ada visited 1 times" );
+  rename.apply_mut( &mut registry );
+  assert_eq!( registry.get::<Name>().unwrap().0, "Ada (1)" );
+}
+
+#[test]
+#[should_panic( expected = "is already borrowed" )]
+fn apply_mut_panics_borrowing_the_same_resource_mutably_twice() {
+  fn twice( _: ResMut<Visits>, _: ResMut<Visits> ) {}
+  twice.apply_mut( &mut registry() );
+}
+
+#[test]
+#[should_panic( expected = "is already borrowed" )]
+fn apply_panics_borrowing_the_same_resource_mutably_twice() {
+  fn twice( _: ResMut<Visits>, _: ResMut<Visits> ) {}
+  twice.apply( &registry() );
+}
+
+#[test]
+fn boxed_systems_with_different_arguments_run_in_a_loop() {
+  fn reset( mut visits: ResMut<Visits> ) { visits.0 = 10; }
+  fn nothing() {}
+  fn check( name: Res<Name>, visits: Res<Visits> ) {
+    assert_eq!( name.0, "Ada" );
+    assert_eq!( visits.0, 10 );
+  }
+
+  let mut systems: Vec<BoxedSystem<TypeRegistry, ()>> = vec![ nothing.into_system(), reset.into_system(), check.into_system() ];
+  systems.push( BoxedSystem::new( |registry: &TypeRegistry| { visit.apply( registry ); } ) );
+
+  let registry = registry();
+  for system in &systems {
+    system.run( &registry );
+  }
+  assert_eq!( registry.get::<Visits>().unwrap().0, 11 );
+  assert_eq!( visit.into_system().run( &registry ), "Ada visited 12 times" );
+}
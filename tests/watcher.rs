@@ -0,0 +1,90 @@
+mod common;
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use aanyx::host::{DylibLoader, DylibPluginManager, LoadError, PluginManagerGet, PluginManagerLoad, PluginWatcher};
+use aanyx::import_plugin;
+use plugin_test_api::Greeter;
+
+const DEBOUNCE: Duration = Duration::from_millis( 50 );
+
+// Copy the greeter plugin in its own directory, so that every test can modify it
+fn greeter_copy( test: &str ) -> PathBuf {
+  let dir = Path::new( env!("CARGO_TARGET_TMPDIR") ).join( "watcher" ).join( test );
+  fs::create_dir_all( &dir ).unwrap();
+  let path = dir.join( common::fixture( "plugin-test-greeter" ).file_name().unwrap() );
+  fs::copy( common::fixture( "plugin-test-greeter" ), &path ).unwrap();
+  path
+}
+
+// Replace the file with a new one, as a linker would do, so the mapped library is never modified in place
+fn rebuild( path: &Path ) {
+  let new = path.with_extension( "new" );
+  fs::copy( common::fixture( "plugin-test-greeter" ), &new ).unwrap();
+  File::options().write( true ).open( &new ).unwrap().set_modified( SystemTime::now() + Duration::from_secs( 10 ) ).unwrap();
+  fs::rename( &new, path ).unwrap();
+}
+
+#[test]
+fn poll_reloads_changed_files_after_the_debounce_interval() {
+  let path = greeter_copy( "reload" );
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+
+  let mut watcher = PluginWatcher::new( DEBOUNCE );
+  watcher.watch_all( manager.libraries().map( Path::to_path_buf ).collect::<Vec<_>>() );
+
+  let mut reloaded = Vec::new();
+  watcher.poll( &mut manager, |path, result: Result<(), LoadError>| reloaded.push( (path.to_path_buf(), result.is_ok()) ) );
+  assert!( reloaded.is_empty() );
+
+  rebuild( &path );
+  watcher.poll( &mut manager, |path, result| reloaded.push( (path.to_path_buf(), result.is_ok()) ) );
+  assert!( reloaded.is_empty(), "the change must be debounced" );
+
+  sleep( DEBOUNCE * 2 );
+  watcher.poll( &mut manager, |path, result| reloaded.push( (path.to_path_buf(), result.is_ok()) ) );
+  assert_eq!( reloaded, [(path.clone(), true)] );
+  assert_eq!( manager.get( &String::from("english") ).unwrap().greet( "Alice" ), "Hello Alice" );
+
+  sleep( DEBOUNCE * 2 );
+  watcher.poll( &mut manager, |path, result| reloaded.push( (path.to_path_buf(), result.is_ok()) ) );
+  assert_eq!( reloaded.len(), 1, "every change is reported once" );
+}
+
+#[test]
+fn poll_as_new_uses_the_given_loader() {
+  let path = greeter_copy( "reload_as_new" );
+  let mut manager = DylibPluginManager::<dyn Greeter>::new( import_plugin!( dyn Greeter ) );
+  manager.load( unsafe { DylibLoader::open( &path ) }.unwrap() ).unwrap();
+
+  let mut watcher = PluginWatcher::new( DEBOUNCE );
+  watcher.watch( &path );
+  rebuild( &path );
+  watcher.changed();
+  sleep( DEBOUNCE * 2 );
+
+  let mut opened = Vec::new();
+  let mut results = Vec::new();
+  watcher.poll_as_new( &mut manager, |path| { opened.push( path.to_path_buf() ); unsafe { DylibLoader::open( path ) } }, |_, result| results.push( result.is_ok() ) );
+  assert_eq!( opened, [path] );
+  assert_eq!( results, [true] );
+  assert_eq!( manager.get( &String::from("italian") ).unwrap().greet( "Bob" ), "Ciao Bob" );
+}
+
+#[test]
+fn unwatched_files_are_not_reported() {
+  let path = greeter_copy( "unwatch" );
+  let mut watcher = PluginWatcher::new( Duration::ZERO );
+  watcher.watch( &path );
+  assert!( watcher.unwatch( &path ) );
+  assert!( !watcher.unwatch( &path ) );
+
+  rebuild( &path );
+  watcher.changed();
+  assert!( watcher.changed().is_empty() );
+  assert_eq!( watcher.watched().count(), 0 );
+}